@@ -1,5 +1,7 @@
+use crate::core::manifest::MANIFEST_FILE;
 use clap::{Parser, Subcommand};
 use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 // #[command(name="vdpm")]
@@ -8,14 +10,138 @@ pub struct Cli {
     pub command: Commands,
 }
 
-#[derive(Subcommand, Debug)]
+#[derive(Subcommand, Debug, Clone)]
 pub enum Commands {
     List,
-    Enable { name: String },
-    Disable { name: String },
-    Install { name: String },
-    Uninstall { name: String },
-    Interactive,
+    Enable {
+        name: String,
+    },
+    Disable {
+        name: String,
+        /// Comment out the import line instead of removing it
+        #[arg(long)]
+        comment: bool,
+    },
+    Install {
+        name: String,
+    },
+    Uninstall {
+        name: String,
+    },
+    /// List installed plugins with a newer version in a registry
+    Outdated,
+    /// Upgrade plugins in place, keeping the previous version for rollback
+    Update {
+        name: Option<String>,
+        /// Update every outdated plugin
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+    /// Swap a plugin back to the version it was updated from
+    Rollback {
+        name: String,
+    },
+    /// Bring back the most recently uninstalled copy of a plugin
+    Restore {
+        name: String,
+    },
+    /// Keep a plugin at its installed version when updating
+    Pin {
+        name: String,
+    },
+    /// Let `update` move a pinned plugin to newer versions again
+    Unpin {
+        name: String,
+    },
+    /// Install, remove, enable and disable plugins to match vdpm.lock exactly
+    Sync,
+    /// Install, remove, enable and disable plugins to match a vdpm.toml manifest
+    Apply {
+        #[arg(long, default_value = MANIFEST_FILE)]
+        manifest: PathBuf,
+        /// Only print the operations that would run
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Report plugin files modified, missing or added since they were installed
+    Verify {
+        name: Option<String>,
+    },
+    /// Show the docstring, keybindings, options and api functions of an installed plugin
+    Explain {
+        name: String,
+    },
+    /// Search the cached registry indexes
+    Search {
+        /// Fuzzy matched against plugin names and descriptions
+        query: Option<String>,
+        #[arg(long)]
+        tag: Option<String>,
+        #[arg(long)]
+        author: Option<String>,
+    },
+    /// Fetch the plugin indexes of all registries and cache them locally
+    Refresh,
+    /// Manage the registries plugins are searched and installed from
+    Registry {
+        #[command(subcommand)]
+        command: RegistryCommands,
+    },
+    Interactive {
+        /// Open the native vdpm sheet of the companion VisiData plugin instead
+        /// of editing plugins.csv
+        #[arg(long)]
+        native: bool,
+    },
+    /// Answer JSON-RPC requests on stdin, one per line, used by the companion VisiData plugin
+    Rpc,
+    /// Answer JSON-RPC requests on a Unix domain socket until interrupted
+    Serve {
+        /// Defaults to vdpm.sock in the vdpm config folder
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RegistryCommands {
+    List,
+    Add {
+        name: String,
+        /// Url or local path of the registry's plugin index
+        url: String,
+        /// Registries with a higher priority are searched first
+        #[arg(long, default_value_t = 0)]
+        priority: i64,
+        /// Environment variable holding a bearer token for the registry
+        #[arg(long)]
+        token_env: Option<String>,
+        /// Refuse plugins without a valid signature from a trusted key
+        #[arg(long)]
+        require_signature: bool,
+    },
+    Remove {
+        name: String,
+    },
+}
+
+impl Commands {
+    /// Whether the command changes installed or enabled plugins, which has to
+    /// be recorded in the lock file.
+    pub fn changes_plugins(&self) -> bool {
+        matches!(
+            self,
+            Commands::Enable { .. }
+                | Commands::Disable { .. }
+                | Commands::Install { .. }
+                | Commands::Uninstall { .. }
+                | Commands::Update { .. }
+                | Commands::Rollback { .. }
+                | Commands::Restore { .. }
+                | Commands::Sync
+                | Commands::Apply { dry_run: false, .. }
+        )
+    }
 }
 
 impl Display for Commands {
@@ -23,10 +149,41 @@ impl Display for Commands {
         match self {
             Commands::List => write!(f, "list"),
             Commands::Enable { name } => write!(f, "enable {}", name),
-            Commands::Disable { name } => write!(f, "disable {}", name),
+            Commands::Disable { name, .. } => write!(f, "disable {}", name),
             Commands::Install { name } => write!(f, "install {}", name),
             Commands::Uninstall { name } => write!(f, "uninstall {}", name),
-            Commands::Interactive => write!(f, "interactive"),
+            Commands::Outdated => write!(f, "outdated"),
+            Commands::Update {
+                name: Some(name), ..
+            } => write!(f, "update {}", name),
+            Commands::Update { name: None, .. } => write!(f, "update --all"),
+            Commands::Rollback { name } => write!(f, "rollback {}", name),
+            Commands::Restore { name } => write!(f, "restore {}", name),
+            Commands::Pin { name } => write!(f, "pin {}", name),
+            Commands::Unpin { name } => write!(f, "unpin {}", name),
+            Commands::Sync => write!(f, "sync"),
+            Commands::Apply { manifest, .. } => write!(f, "apply {}", manifest.display()),
+            Commands::Verify { name: Some(name) } => write!(f, "verify {}", name),
+            Commands::Verify { name: None } => write!(f, "verify"),
+            Commands::Explain { name } => write!(f, "explain {}", name),
+            Commands::Search { query, .. } => {
+                write!(f, "search {}", query.as_deref().unwrap_or_default())
+            }
+            Commands::Refresh => write!(f, "refresh"),
+            Commands::Registry { command } => write!(f, "registry {}", command),
+            Commands::Interactive { .. } => write!(f, "interactive"),
+            Commands::Rpc => write!(f, "rpc"),
+            Commands::Serve { .. } => write!(f, "serve"),
+        }
+    }
+}
+
+impl Display for RegistryCommands {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryCommands::List => write!(f, "list"),
+            RegistryCommands::Add { name, url, .. } => write!(f, "add {} {}", name, url),
+            RegistryCommands::Remove { name } => write!(f, "remove {}", name),
         }
     }
 }
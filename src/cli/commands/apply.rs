@@ -0,0 +1,230 @@
+use crate::cli::commands::install::select_registries;
+use crate::config_loader::{self, AppConfig};
+use crate::core::index::{self, ResolvedPlugin};
+use crate::core::lockfile::LockFile;
+use crate::core::manifest::{Manifest, ManifestPlugin};
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+use crate::core::version::VersionReq;
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::get_cache_folder_path;
+use crate::interactive::event_dispatcher::{
+    PluginOperation, generate_operations, rollback_batch, run_operations,
+};
+use crate::sources::InstallSource;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tabled::{Table, Tabled};
+use tracing::{info, warn};
+
+#[derive(Debug, Tabled)]
+struct PlannedOperation {
+    plugin: String,
+    operation: String,
+}
+
+/// Whether the installed plugin came from the source the manifest asks for.
+/// Registry installs are locked as `<registry>/<name>@<version>`, so only the
+/// registry is compared for them, the version is up to the constraint.
+fn source_matches(source: &InstallSource, installed_source: Option<&str>) -> bool {
+    match source {
+        InstallSource::Index {
+            registry: Some(registry),
+            ..
+        } => installed_source.is_some_and(|installed_source| {
+            installed_source.starts_with(&format!("{}/", registry))
+        }),
+        InstallSource::Index { registry: None, .. } => true,
+        source => installed_source == Some(source.to_string().as_str()),
+    }
+}
+
+async fn resolve(
+    config: &AppConfig,
+    registry: Option<&str>,
+    plugin_name: &str,
+    version_req: &VersionReq,
+) -> Result<ResolvedPlugin> {
+    index::resolve_matching(
+        &select_registries(config, registry)?,
+        &get_cache_folder_path()?.join("index"),
+        plugin_name,
+        version_req,
+    )
+    .await
+}
+
+/// The plugin as the manifest wants it. An installed plugin that already
+/// satisfies the manifest is kept as is, so applying twice is a no-op. A
+/// relative local `source` is resolved against `manifest_folder`.
+async fn desired_plugin(
+    config: &AppConfig,
+    manifest_plugin: &ManifestPlugin,
+    manifest_folder: &Path,
+    installed_plugin: Option<&Plugin>,
+) -> Result<Plugin> {
+    let source = manifest_plugin
+        .source
+        .as_deref()
+        .map(|source| InstallSource::parse_in(source, manifest_folder))
+        .transpose()?
+        .unwrap_or_else(|| InstallSource::Index {
+            registry: None,
+            name: manifest_plugin.name.clone(),
+            version: None,
+        });
+    let mut version_req = manifest_plugin.version_req()?;
+
+    if let InstallSource::Index { name, version, .. } = &source {
+        if *name != manifest_plugin.name {
+            return Err(VDPMError::InvalidArguments(format!(
+                "plugin({}) has the source of plugin({})",
+                manifest_plugin.name, name
+            )));
+        }
+        if let Some(version) = version {
+            version_req = Some(VersionReq::parse(&format!("={}", version))?);
+        }
+    }
+
+    if let Some(installed_plugin) = installed_plugin
+        && source_matches(&source, installed_plugin.source.as_deref())
+    {
+        let version_matches = match (&version_req, &installed_plugin.version) {
+            (None, _) => true,
+            (Some(version_req), Some(version)) => version_req.matches(version),
+            (Some(_), None) => false,
+        };
+        // Only registry installs can be moved to another version.
+        if version_matches || !matches!(source, InstallSource::Index { .. }) {
+            if !version_matches {
+                warn!(
+                    "Plugin({}) version does not satisfy {}, but its source has no other versions",
+                    manifest_plugin.name,
+                    manifest_plugin.version.as_deref().unwrap_or_default()
+                );
+            }
+            return Ok(Plugin {
+                enabled: manifest_plugin.enabled,
+                ..installed_plugin.clone()
+            });
+        }
+    }
+
+    let (source, version) = match source {
+        InstallSource::Index { registry, name, .. } => {
+            let version_req = version_req.unwrap_or(VersionReq::parse("*")?);
+            let resolved = resolve(config, registry.as_deref(), &name, &version_req).await?;
+            (
+                format!(
+                    "{}/{}@{}",
+                    resolved.registry, name, resolved.version.version
+                ),
+                Some(resolved.version.version),
+            )
+        }
+        source => (source.to_string(), None),
+    };
+    Ok(Plugin {
+        name: manifest_plugin.name.clone(),
+        enabled: manifest_plugin.enabled,
+        installed: true,
+        version,
+        source: Some(source),
+        ..Default::default()
+    })
+}
+
+/// Converges the installed plugins to the manifest at `manifest_path`, or
+/// only prints the operations that would run with `dry_run`. Url, git and
+/// local sources name the plugin after what they install, a source that
+/// installs a plugin under another name than in the manifest rolls the
+/// whole apply back.
+pub async fn execute(manifest_path: &Path, dry_run: bool) -> Result<Table> {
+    info!("Applying manifest {}!", manifest_path.display());
+    let config: AppConfig = config_loader::load_or_create()?;
+    let manifest = Manifest::from_file(manifest_path).await?;
+    let manifest_folder = manifest_path.parent().unwrap_or(Path::new(""));
+
+    let installed_registry = LockFile::generate().await?.to_registry();
+    let mut plugins: BTreeMap<String, Plugin> = BTreeMap::new();
+    for manifest_plugin in &manifest.plugins {
+        let plugin = desired_plugin(
+            &config,
+            manifest_plugin,
+            manifest_folder,
+            installed_registry.plugins.get(&manifest_plugin.name),
+        )
+        .await?;
+        plugins.insert(plugin.name.clone(), plugin);
+    }
+    let desired_registry = Registry { plugins };
+
+    let operations: Vec<PluginOperation> =
+        generate_operations(&installed_registry, &desired_registry);
+    if !dry_run {
+        let transaction = run_operations(&operations).await?;
+        let applied_registry = Registry::generate().await?;
+        if let Some(manifest_plugin) = manifest
+            .plugins
+            .iter()
+            .find(|manifest_plugin| !applied_registry.plugins.contains_key(&manifest_plugin.name))
+        {
+            return Err(rollback_batch(
+                transaction,
+                format!("apply {}", manifest_path.display()),
+                VDPMError::InvalidPlugin(
+                    manifest_plugin.name.clone(),
+                    format!(
+                        "{} does not install a plugin of that name",
+                        manifest_plugin
+                            .source
+                            .as_deref()
+                            .unwrap_or(&manifest_plugin.name)
+                    ),
+                ),
+            ));
+        }
+    }
+    let planned_operations: Vec<PlannedOperation> = operations
+        .into_iter()
+        .map(|operation| PlannedOperation {
+            plugin: operation.plugin.name,
+            operation: operation.command.to_string(),
+        })
+        .collect();
+
+    Ok(Table::new(planned_operations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_matches() {
+        let registry_source = InstallSource::parse("internal/hello").unwrap();
+        assert!(source_matches(
+            &registry_source,
+            Some("internal/hello@0.2.0")
+        ));
+        assert!(!source_matches(
+            &registry_source,
+            Some("official/hello@0.2.0")
+        ));
+        assert!(source_matches(
+            &InstallSource::parse("hello").unwrap(),
+            None
+        ));
+
+        let git_source = InstallSource::parse("git+file:///srv/hello.git@v1").unwrap();
+        assert!(source_matches(
+            &git_source,
+            Some("git+file:///srv/hello.git@v1")
+        ));
+        assert!(!source_matches(
+            &git_source,
+            Some("git+file:///srv/hello.git@v2")
+        ));
+    }
+}
@@ -1,10 +1,22 @@
+use crate::config_loader::{self, AppConfig};
 use crate::core::plugin::Plugin;
-use crate::error::Result;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::visidatarc;
+use crate::utils::get_home_dir;
 use tabled::Table;
 use tracing::info;
 
-pub async fn execute(name: &str) -> Result<Table> {
+pub async fn execute(name: &str, comment_out: bool) -> Result<Table> {
     info!("Disable plugin({})!", name);
-    // TODO delete the corresponding line from .visidatarc
-    Ok(Table::new(Vec::<Plugin>::new()))
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let rc_file_path = get_home_dir().join(&config.settings.rc_file);
+    if !visidatarc::disable_plugin(&rc_file_path, name, comment_out).await? {
+        return Err(VDPMError::PluginNotEnabled(name.to_string()));
+    }
+
+    let registry: Registry = Registry::generate().await?;
+    let plugins: Vec<Plugin> = registry.plugins.get(name).cloned().into_iter().collect();
+    Ok(Table::new(plugins))
 }
@@ -1,13 +1,26 @@
-use crate::config_loader::AppConfig;
+use crate::config_loader::{self, AppConfig};
 use crate::core::plugin::Plugin;
-use crate::core::registry::{self, Registry};
-use crate::error::Result;
-use crate::fs::paths::get_registry_file_path;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::visidatarc;
+use crate::utils::get_home_dir;
 use tabled::Table;
 use tracing::info;
 
 pub async fn execute(name: &str) -> Result<Table> {
     info!("Enable plugin({})!", name);
-    // TODO add a line for our plugin to .visidatarc
-    Ok(Table::new(Vec::<Plugin>::new()))
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    if !Registry::get_installed_plugins()?.contains(name) {
+        return Err(VDPMError::PluginNotInstalled(name.to_string()));
+    }
+
+    let rc_file_path = get_home_dir().join(&config.settings.rc_file);
+    if !visidatarc::enable_plugin(&rc_file_path, name).await? {
+        info!("Plugin({}) is already enabled!", name);
+    }
+
+    let registry: Registry = Registry::generate().await?;
+    let plugins: Vec<Plugin> = registry.plugins.get(name).cloned().into_iter().collect();
+    Ok(Table::new(plugins))
 }
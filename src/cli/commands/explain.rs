@@ -0,0 +1,95 @@
+use crate::config_loader::{self, AppConfig};
+use crate::core::explain::{self, PluginReport};
+use crate::error::{Result, VDPMError};
+use crate::utils::get_home_dir;
+use std::path::{Path, PathBuf};
+use tabled::{Table, Tabled};
+use tracing::info;
+
+#[derive(Debug, Tabled)]
+struct ExplainRow {
+    section: String,
+    name: String,
+    keys: String,
+    description: String,
+}
+
+/// Python files of a plugin module or package, in a stable order.
+fn plugin_source_files(plugin_folder: &Path, name: &str) -> Result<Vec<PathBuf>> {
+    let module = plugin_folder.join(format!("{}.py", name));
+    if module.is_file() {
+        return Ok(vec![module]);
+    }
+
+    let package = plugin_folder.join(name);
+    if !package.join("__init__.py").is_file() {
+        return Err(VDPMError::PluginNotInstalled(name.to_string()));
+    }
+
+    let mut folders = vec![package];
+    let mut files = Vec::new();
+    while let Some(folder) = folders.pop() {
+        let entries = std::fs::read_dir(&folder).map_err(|e| {
+            VDPMError::PluginFileError(format!("Failed to read {}", folder.display()), e)
+        })?;
+        for path in entries
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+        {
+            if path.is_dir() {
+                folders.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "py") {
+                files.push(path);
+            }
+        }
+    }
+    // __init__.py first, so its docstring describes the package.
+    files.sort_by_key(|path| (!path.ends_with("__init__.py"), path.clone()));
+    Ok(files)
+}
+
+fn report_rows(report: PluginReport) -> Vec<ExplainRow> {
+    let mut rows = vec![ExplainRow {
+        section: "docstring".into(),
+        name: String::new(),
+        keys: String::new(),
+        description: report
+            .docstring
+            .unwrap_or_else(|| "(no module docstring)".into()),
+    }];
+    rows.extend(report.commands.into_iter().map(|command| ExplainRow {
+        section: format!("command({})", command.sheet),
+        name: command.longname,
+        keys: command.keystrokes,
+        description: command.help,
+    }));
+    rows.extend(report.options.into_iter().map(|option| ExplainRow {
+        section: "option".into(),
+        name: option.name,
+        keys: format!("default: {}", option.default),
+        description: option.help,
+    }));
+    rows.extend(report.api_functions.into_iter().map(|function| ExplainRow {
+        section: format!("api({})", function.class),
+        name: function.name,
+        keys: String::new(),
+        description: function.help,
+    }));
+    rows
+}
+
+pub async fn execute(name: &str) -> Result<Table> {
+    info!("Explain plugin({})!", name);
+    let config: AppConfig = config_loader::load_or_create()?;
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+
+    let mut report = PluginReport::default();
+    for source_file in plugin_source_files(&plugin_folder, name)? {
+        let source = tokio::fs::read_to_string(&source_file).await.map_err(|e| {
+            VDPMError::PluginFileError(format!("Failed to read {}", source_file.display()), e)
+        })?;
+        report.extend(explain::explain(&source));
+    }
+
+    Ok(Table::new(report_rows(report)))
+}
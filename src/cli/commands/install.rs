@@ -1,13 +1,262 @@
-use crate::config_loader::AppConfig;
+use crate::config_loader::{self, AppConfig, RegistryConfig};
+use crate::core::index::{self, ResolvedPlugin};
+use crate::core::install_record::{InstallRecord, InstallRecords};
 use crate::core::plugin::Plugin;
-use crate::core::registry::{self, Registry};
-use crate::error::Result;
-use crate::fs::paths::get_registry_file_path;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::checksum::sha256_plugin_files;
+use crate::fs::paths::{get_cache_folder_path, get_install_records_file_path};
+use crate::sources::{InstallSource, Integrity, git, http, local};
+use crate::utils::get_home_dir;
+use chrono::Local;
+use std::path::Path;
 use tabled::Table;
 use tracing::info;
 
+/// What an install produced, before it is recorded.
+pub(crate) struct Installed {
+    pub plugin_name: String,
+    pub source: String,
+    pub commit: Option<String>,
+    pub version: Option<String>,
+    pub registry: Option<String>,
+    pub signed_by: Option<String>,
+}
+
 pub async fn execute(name: &str) -> Result<Table> {
     info!("Install plugin({})!", name);
-    // TODO copy the install logic from github
-    Ok(Table::new(Vec::<Plugin>::new()))
+    let config: AppConfig = config_loader::load_or_create()?;
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+
+    let source = InstallSource::parse(name)?;
+    let installed = match source {
+        InstallSource::Index {
+            registry,
+            name,
+            version,
+        } => {
+            let resolved =
+                resolve_from_registries(&config, registry.as_deref(), &name, version.as_deref())
+                    .await?;
+            install_resolved(&config, &resolved, &plugin_folder).await?
+        }
+        source => install_from(&source, &plugin_folder, None, &Integrity::default(), None).await?,
+    };
+    info!(
+        "Plugin({}) is installed from {}!",
+        installed.plugin_name, installed.source
+    );
+    record_install(&installed, &plugin_folder).await?;
+
+    let registry: Registry = Registry::generate().await?;
+    let plugins: Vec<Plugin> = registry
+        .plugins
+        .get(&installed.plugin_name)
+        .cloned()
+        .into_iter()
+        .collect();
+    Ok(Table::new(plugins))
+}
+
+/// Records where the plugin came from together with the sha256 of each of its
+/// files, which `vdpm verify` checks them against later.
+pub(crate) async fn record_install(installed: &Installed, plugin_folder: &Path) -> Result<()> {
+    let files = sha256_plugin_files(plugin_folder, &installed.plugin_name).map_err(|e| {
+        VDPMError::PluginFileError(
+            format!("Failed to hash files of plugin({})", installed.plugin_name),
+            e,
+        )
+    })?;
+    let install_records_file_path = get_install_records_file_path()?;
+    let mut install_records = InstallRecords::from_file(&install_records_file_path).await?;
+    install_records.plugins.insert(
+        installed.plugin_name.clone(),
+        InstallRecord {
+            source: installed.source.clone(),
+            installed_at: Local::now().to_rfc3339(),
+            commit: installed.commit.clone(),
+            version: installed.version.clone(),
+            registry: installed.registry.clone(),
+            files,
+            signed_by: installed.signed_by.clone(),
+            pinned: false,
+        },
+    );
+    install_records.to_file(&install_records_file_path).await?;
+    Ok(())
+}
+
+async fn install_from(
+    source: &InstallSource,
+    plugin_folder: &Path,
+    plugin_name: Option<&str>,
+    integrity: &Integrity,
+    token: Option<&str>,
+) -> Result<Installed> {
+    let (installed_name, commit, signed_by) = match source {
+        InstallSource::Local(path) => {
+            if !integrity.is_empty() && !path.is_file() {
+                return Err(VDPMError::InvalidPlugin(
+                    path.display().to_string(),
+                    "sha256 and signatures can only be checked for files and archives".into(),
+                ));
+            }
+            let signed_by = integrity.check(path)?;
+            (
+                local::install(path, plugin_folder, plugin_name)?,
+                None,
+                signed_by,
+            )
+        }
+        InstallSource::Url(url) => {
+            let download = http::install(url, token, plugin_folder, plugin_name, integrity).await?;
+            (download.plugin_name, None, download.signed_by)
+        }
+        InstallSource::Git { url, git_ref } => {
+            if integrity.signature.is_some() {
+                return Err(VDPMError::SignatureError(
+                    source.to_string(),
+                    "git sources can not be signature verified".into(),
+                ));
+            }
+            if integrity.sha256.is_some() {
+                return Err(VDPMError::InvalidPlugin(
+                    source.to_string(),
+                    "sha256 can not be checked for git sources, pin them by commit".into(),
+                ));
+            }
+            let checkout = git::install(
+                url,
+                git_ref.as_deref(),
+                &get_cache_folder_path()?.join("git"),
+                plugin_folder,
+                plugin_name,
+            )
+            .await?;
+            (checkout.plugin_name, Some(checkout.commit), None)
+        }
+        InstallSource::Index { .. } => {
+            return Err(VDPMError::UnsupportedSource(source.to_string()));
+        }
+    };
+
+    Ok(Installed {
+        plugin_name: installed_name,
+        source: source.to_string(),
+        commit,
+        version: None,
+        registry: None,
+        signed_by,
+    })
+}
+
+/// The configured registry a plugin was resolved from.
+fn resolved_registry<'a>(
+    config: &'a AppConfig,
+    resolved: &ResolvedPlugin,
+) -> Option<&'a RegistryConfig> {
+    config
+        .registries
+        .iter()
+        .find(|registry| registry.name == resolved.registry)
+}
+
+/// The checks a plugin resolved from a registry has to pass. The signature is
+/// fetched when the index lists one, or from `<url>.minisig` when the registry
+/// requires signed plugins.
+async fn resolved_integrity(config: &AppConfig, resolved: &ResolvedPlugin) -> Result<Integrity> {
+    let registry = resolved_registry(config, resolved);
+    let require_signature = registry.is_some_and(|registry| registry.require_signature);
+
+    let signature_location = resolved
+        .version
+        .signature
+        .clone()
+        .or_else(|| require_signature.then(|| format!("{}.minisig", resolved.version.url)));
+    let signature = match signature_location {
+        Some(signature_location) => {
+            let token = registry.and_then(|registry| registry.token());
+            let signature = http::fetch_location(&signature_location, token.as_deref()).await?;
+            Some(String::from_utf8_lossy(&signature).to_string())
+        }
+        None => None,
+    };
+    if signature.is_some() && config.trusted_keys.is_empty() {
+        return Err(VDPMError::SignatureError(
+            resolved.entry.name.clone(),
+            "no [[trusted_keys]] are configured".into(),
+        ));
+    }
+
+    Ok(Integrity {
+        sha256: resolved.version.sha256.clone(),
+        signature,
+        trusted_keys: config.trusted_keys.clone(),
+    })
+}
+
+/// Installs the version of a plugin resolved from a registry index, after
+/// checking it against the sha256 and signature the registry publishes. The
+/// download is authenticated with the token of the registry.
+pub(crate) async fn install_resolved(
+    config: &AppConfig,
+    resolved: &ResolvedPlugin,
+    plugin_folder: &Path,
+) -> Result<Installed> {
+    let integrity = resolved_integrity(config, resolved).await?;
+    let token = resolved_registry(config, resolved).and_then(|registry| registry.token());
+    let installed = install_from(
+        &InstallSource::parse(&resolved.version.url)?,
+        plugin_folder,
+        Some(&resolved.entry.name),
+        &integrity,
+        token.as_deref(),
+    )
+    .await?;
+
+    Ok(Installed {
+        version: Some(resolved.version.version.clone()),
+        registry: Some(resolved.registry.clone()),
+        ..installed
+    })
+}
+
+/// The registries a plugin is looked up in: only `registry` if given,
+/// otherwise all of them in priority order.
+pub(crate) fn select_registries<'a>(
+    config: &'a AppConfig,
+    registry: Option<&str>,
+) -> Result<Vec<&'a RegistryConfig>> {
+    match registry {
+        Some(registry) => Ok(vec![
+            config
+                .registries
+                .iter()
+                .find(|registry_config| registry_config.name == registry)
+                .ok_or_else(|| VDPMError::UnknownRegistry(registry.to_string()))?,
+        ]),
+        None => Ok(config.registries_by_priority()),
+    }
+}
+
+/// Looks the plugin up in the cached registry indexes, in priority order or
+/// only in `registry` if given, at the requested version or the latest one.
+pub(crate) async fn resolve_from_registries(
+    config: &AppConfig,
+    registry: Option<&str>,
+    plugin_name: &str,
+    version: Option<&str>,
+) -> Result<ResolvedPlugin> {
+    let resolved = index::resolve(
+        &select_registries(config, registry)?,
+        &get_cache_folder_path()?.join("index"),
+        plugin_name,
+        version,
+    )
+    .await?;
+    info!(
+        "Plugin({}@{}) is found in registry({})",
+        plugin_name, resolved.version.version, resolved.registry
+    );
+    Ok(resolved)
 }
@@ -1,5 +1,17 @@
+pub mod apply;
 pub mod disable;
 pub mod enable;
+pub mod explain;
 pub mod install;
 pub mod list;
+pub mod outdated;
+pub mod pin;
+pub mod refresh;
+pub mod registry;
+pub mod restore;
+pub mod rollback;
+pub mod search;
+pub mod sync;
 pub mod uninstall;
+pub mod update;
+pub mod verify;
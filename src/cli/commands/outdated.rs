@@ -0,0 +1,61 @@
+use crate::cli::commands::install::resolve_from_registries;
+use crate::config_loader::{self, AppConfig};
+use crate::core::install_record::InstallRecords;
+use crate::core::registry::Registry;
+use crate::core::version::is_newer;
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::get_install_records_file_path;
+use tabled::{Table, Tabled};
+use tracing::{info, warn};
+
+#[derive(Debug, Tabled)]
+pub(crate) struct OutdatedPlugin {
+    pub name: String,
+    pub installed: String,
+    pub latest: String,
+    pub registry: String,
+}
+
+/// Installed plugins for which a registry lists a newer version. Plugins
+/// without a known version are reported too, as they can not be compared.
+pub(crate) async fn find_outdated(config: &AppConfig) -> Result<Vec<OutdatedPlugin>> {
+    let registry: Registry = Registry::generate().await?;
+    let install_records = InstallRecords::from_file(&get_install_records_file_path()?).await?;
+
+    let mut outdated = Vec::new();
+    for plugin in registry.plugins.values() {
+        let record_registry = install_records
+            .plugins
+            .get(&plugin.name)
+            .and_then(|record| record.registry.as_deref());
+        let resolved =
+            match resolve_from_registries(config, record_registry, &plugin.name, None).await {
+                Ok(resolved) => resolved,
+                Err(VDPMError::PluginNotFound(_)) => continue,
+                Err(e) => {
+                    warn!("Plugin({}) could not be looked up: {}", plugin.name, e);
+                    continue;
+                }
+            };
+
+        let is_outdated = plugin
+            .version
+            .as_ref()
+            .is_none_or(|version| is_newer(&resolved.version.version, version));
+        if is_outdated {
+            outdated.push(OutdatedPlugin {
+                name: plugin.name.clone(),
+                installed: plugin.version.clone().unwrap_or_else(|| "unknown".into()),
+                latest: resolved.version.version,
+                registry: resolved.registry,
+            });
+        }
+    }
+    Ok(outdated)
+}
+
+pub async fn execute() -> Result<Table> {
+    info!("Listing outdated plugins!");
+    let config: AppConfig = config_loader::load_or_create()?;
+    Ok(Table::new(find_outdated(&config).await?))
+}
@@ -0,0 +1,27 @@
+use crate::core::install_record::InstallRecords;
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::get_install_records_file_path;
+use tabled::Table;
+use tracing::info;
+
+/// Pins or unpins a plugin vdpm installed. A pinned plugin keeps its version,
+/// `vdpm update` refuses it and `vdpm update --all` skips it.
+pub async fn execute(name: &str, pinned: bool) -> Result<Table> {
+    info!("Set pinned of plugin({}) to {}!", name, pinned);
+    let install_records_file_path = get_install_records_file_path()?;
+    let mut install_records = InstallRecords::from_file(&install_records_file_path).await?;
+    let install_record = install_records.plugins.get_mut(name).ok_or_else(|| {
+        VDPMError::InvalidArguments(format!(
+            "plugin({}) was not installed by vdpm, it has no version to pin",
+            name
+        ))
+    })?;
+    install_record.pinned = pinned;
+    install_records.to_file(&install_records_file_path).await?;
+
+    let registry: Registry = Registry::generate().await?;
+    let plugins: Vec<Plugin> = registry.plugins.get(name).cloned().into_iter().collect();
+    Ok(Table::new(plugins))
+}
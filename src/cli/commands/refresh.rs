@@ -0,0 +1,43 @@
+use crate::config_loader::{self, AppConfig};
+use crate::core::index::{self, PluginIndex};
+use crate::error::Result;
+use crate::fs::paths::get_cache_folder_path;
+use tabled::{Table, Tabled};
+use tracing::info;
+
+#[derive(Debug, Tabled)]
+struct IndexSummary {
+    registry: String,
+    url: String,
+    priority: i64,
+    schema_version: u32,
+    plugins: usize,
+}
+
+pub async fn execute() -> Result<Table> {
+    info!("Refreshing registry indexes!");
+    let config: AppConfig = config_loader::load_or_create()?;
+    let index_cache_folder = get_cache_folder_path()?.join("index");
+
+    let mut summaries = Vec::new();
+    for registry in config.registries_by_priority() {
+        let index = PluginIndex::fetch(&registry.url, registry.token().as_deref()).await?;
+        let cache_path = index::cache_file_path(&index_cache_folder, &registry.name);
+        index.to_cache(&cache_path).await?;
+        info!(
+            "Plugin index of registry({}) is cached to {}",
+            registry.name,
+            cache_path.display()
+        );
+
+        summaries.push(IndexSummary {
+            registry: registry.name.clone(),
+            url: registry.url.clone(),
+            priority: registry.priority,
+            schema_version: index.schema_version,
+            plugins: index.plugins.len(),
+        });
+    }
+
+    Ok(Table::new(summaries))
+}
@@ -0,0 +1,66 @@
+use crate::cli::args::RegistryCommands;
+use crate::config_loader::{self, AppConfig, CONFIG_FILE, RegistryConfig};
+use crate::error::{Result, VDPMError};
+use crate::sources::is_valid_registry_name;
+use std::path::Path;
+use tabled::{Table, Tabled};
+use tracing::info;
+
+#[derive(Debug, Tabled)]
+struct RegistryRow {
+    name: String,
+    url: String,
+    priority: i64,
+    token_env: String,
+    require_signature: bool,
+}
+
+pub async fn execute(command: &RegistryCommands) -> Result<Table> {
+    let config_path = Path::new(CONFIG_FILE);
+    match command {
+        RegistryCommands::Add {
+            name,
+            url,
+            priority,
+            token_env,
+            require_signature,
+        } => {
+            info!("Add registry({})!", name);
+            if !is_valid_registry_name(name) {
+                return Err(VDPMError::InvalidConfig(format!(
+                    "registry name({}) may only contain letters, digits, '-' and '_'",
+                    name
+                )));
+            }
+            config_loader::add_registry(
+                config_path,
+                &RegistryConfig {
+                    name: name.clone(),
+                    url: url.clone(),
+                    priority: *priority,
+                    token_env: token_env.clone(),
+                    require_signature: *require_signature,
+                },
+            )?;
+        }
+        RegistryCommands::Remove { name } => {
+            info!("Remove registry({})!", name);
+            config_loader::remove_registry(config_path, name)?;
+        }
+        RegistryCommands::List => info!("Listing all registries!"),
+    }
+
+    let config: AppConfig = config_loader::load_or_create()?;
+    let rows: Vec<RegistryRow> = config
+        .registries_by_priority()
+        .into_iter()
+        .map(|registry| RegistryRow {
+            name: registry.name.clone(),
+            url: registry.url.clone(),
+            priority: registry.priority,
+            token_env: registry.token_env.clone().unwrap_or_default(),
+            require_signature: registry.require_signature,
+        })
+        .collect();
+    Ok(Table::new(rows))
+}
@@ -0,0 +1,36 @@
+use crate::config_loader::{self, AppConfig};
+use crate::core::install_record::InstallRecords;
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+use crate::error::Result;
+use crate::fs::paths::{get_install_records_file_path, get_trash_folder_path};
+use crate::fs::{trash, visidatarc};
+use crate::utils::get_home_dir;
+use tabled::Table;
+use tracing::info;
+
+pub async fn execute(name: &str) -> Result<Table> {
+    info!("Restore plugin({})!", name);
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+    let trash_entry = trash::restore_from_trash(&get_trash_folder_path()?, &plugin_folder, name)?;
+
+    if let Some(install_record) = trash_entry.install_record {
+        let install_records_file_path = get_install_records_file_path()?;
+        let mut install_records = InstallRecords::from_file(&install_records_file_path).await?;
+        install_records
+            .plugins
+            .insert(name.to_string(), install_record);
+        install_records.to_file(&install_records_file_path).await?;
+    }
+
+    if trash_entry.enabled {
+        let rc_file_path = get_home_dir().join(&config.settings.rc_file);
+        visidatarc::enable_plugin(&rc_file_path, name).await?;
+    }
+
+    let registry: Registry = Registry::generate().await?;
+    let plugins: Vec<Plugin> = registry.plugins.get(name).cloned().into_iter().collect();
+    Ok(Table::new(plugins))
+}
@@ -0,0 +1,57 @@
+use crate::config_loader::{self, AppConfig};
+use crate::core::install_record::InstallRecords;
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::{get_backups_folder_path, get_install_records_file_path};
+use crate::fs::trash;
+use crate::utils::get_home_dir;
+use tabled::Table;
+use tracing::{error, info};
+
+/// Swaps the installed plugin with the version it was updated from. The
+/// swapped out version becomes the new backup, so a rollback can be undone by
+/// rolling back again.
+pub async fn execute(name: &str) -> Result<Table> {
+    info!("Rollback plugin({})!", name);
+    let config: AppConfig = config_loader::load_or_create()?;
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+    let backups_folder = get_backups_folder_path()?;
+
+    let registry: Registry = Registry::generate().await?;
+    let plugin = registry
+        .plugins
+        .get(name)
+        .ok_or_else(|| VDPMError::PluginNotInstalled(name.to_string()))?;
+    let previous_folder = trash::find_latest_trash_entry_folder(&backups_folder, name)
+        .ok_or_else(|| VDPMError::PluginNotInTrash(name.to_string()))?;
+
+    let install_records_file_path = get_install_records_file_path()?;
+    let mut install_records = InstallRecords::from_file(&install_records_file_path).await?;
+    let current_folder = trash::move_to_trash(
+        &backups_folder,
+        &plugin_folder,
+        name,
+        plugin.enabled,
+        install_records.plugins.remove(name),
+    )?;
+    let previous = match trash::restore_trash_entry(&previous_folder, &plugin_folder, name) {
+        Ok(previous) => previous,
+        Err(e) => {
+            error!("Rolling back plugin({}) failed, restoring it: {}", name, e);
+            trash::restore_trash_entry(&current_folder, &plugin_folder, name)?;
+            return Err(e);
+        }
+    };
+
+    if let Some(install_record) = previous.install_record {
+        install_records
+            .plugins
+            .insert(name.to_string(), install_record);
+    }
+    install_records.to_file(&install_records_file_path).await?;
+
+    let registry: Registry = Registry::generate().await?;
+    let plugins: Vec<Plugin> = registry.plugins.get(name).cloned().into_iter().collect();
+    Ok(Table::new(plugins))
+}
@@ -0,0 +1,65 @@
+use crate::config_loader::{self, AppConfig};
+use crate::core::index;
+use crate::core::registry::Registry;
+use crate::core::search::{self, SearchQuery};
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::get_cache_folder_path;
+use serde::Serialize;
+use tabled::{Table, Tabled};
+use tracing::info;
+
+#[derive(Debug, Serialize, Tabled)]
+pub(crate) struct SearchRow {
+    registry: String,
+    name: String,
+    version: String,
+    description: String,
+    author: String,
+    tags: String,
+    installed: bool,
+    enabled: bool,
+}
+
+/// The plugins of the cached indexes matching `query`, with their local state.
+pub(crate) async fn find(query: &SearchQuery) -> Result<Vec<SearchRow>> {
+    info!("Searching plugins({:?})!", query);
+    let config: AppConfig = config_loader::load_or_create()?;
+    let registries = config.registries_by_priority();
+
+    let indexes =
+        index::load_cached_indexes(&registries, &get_cache_folder_path()?.join("index")).await?;
+    if indexes.is_empty() && !registries.is_empty() {
+        let registry_names: Vec<&str> = registries
+            .iter()
+            .map(|registry| registry.name.as_str())
+            .collect();
+        return Err(VDPMError::IndexNotCached(registry_names.join(", ")));
+    }
+
+    let registry: Registry = Registry::generate().await?;
+    let rows: Vec<SearchRow> = search::search(&indexes, query)
+        .into_iter()
+        .map(|hit| {
+            let local_plugin = registry.plugins.get(&hit.entry.name);
+            SearchRow {
+                registry: hit.registry,
+                version: hit
+                    .entry
+                    .latest_version()
+                    .map(|version| version.version.clone())
+                    .unwrap_or_default(),
+                description: hit.entry.description,
+                author: hit.entry.author,
+                tags: hit.entry.tags.join(", "),
+                installed: local_plugin.is_some_and(|plugin| plugin.installed),
+                enabled: local_plugin.is_some_and(|plugin| plugin.enabled),
+                name: hit.entry.name,
+            }
+        })
+        .collect();
+    Ok(rows)
+}
+
+pub async fn execute(query: SearchQuery) -> Result<Table> {
+    Ok(Table::new(find(&query).await?))
+}
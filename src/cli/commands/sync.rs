@@ -0,0 +1,64 @@
+use crate::core::lockfile::LockFile;
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::get_lock_file_path;
+use crate::interactive::event_dispatcher::{
+    PluginOperation, generate_operations, rollback_batch, run_operations,
+};
+use tabled::{Table, Tabled};
+use tracing::{info, warn};
+
+#[derive(Debug, Tabled)]
+struct SyncedOperation {
+    plugin: String,
+    operation: String,
+}
+
+/// Installs, uninstalls, enables and disables plugins until the plugin folder
+/// and .visidatarc match `vdpm.lock`. Plugins whose files differ from their
+/// locked sha256 are reinstalled, and when they still differ afterwards the
+/// whole sync is rolled back.
+pub async fn execute() -> Result<Table> {
+    let lock_file_path = get_lock_file_path()?;
+    info!("Syncing plugins with {}!", lock_file_path.display());
+    let lock_file = LockFile::from_file(&lock_file_path).await?;
+
+    let current_lock_file = LockFile::generate().await?;
+    let operations: Vec<PluginOperation> =
+        generate_operations(&current_lock_file.to_registry(), &lock_file.to_registry());
+
+    let transaction = run_operations(&operations).await?;
+    let synced_operations: Vec<SyncedOperation> = operations
+        .into_iter()
+        .map(|operation| SyncedOperation {
+            plugin: operation.plugin.name,
+            operation: operation.command.to_string(),
+        })
+        .collect();
+
+    let synced_lock_file = LockFile::generate().await?;
+    let mut mismatch: Option<VDPMError> = None;
+    for locked_plugin in &lock_file.plugins {
+        let Some(locked_sha256) = &locked_plugin.sha256 else {
+            continue;
+        };
+        let synced_sha256 = synced_lock_file
+            .get(&locked_plugin.name)
+            .and_then(|synced_plugin| synced_plugin.sha256.clone());
+        if synced_sha256.as_ref() != Some(locked_sha256) {
+            warn!(
+                "Plugin({}) files differ from the locked sha256 {}",
+                locked_plugin.name, locked_sha256
+            );
+            mismatch.get_or_insert(VDPMError::ChecksumMismatch(
+                format!("plugin({})", locked_plugin.name),
+                locked_sha256.clone(),
+                synced_sha256.unwrap_or_else(|| "no files".into()),
+            ));
+        }
+    }
+    if let Some(mismatch) = mismatch {
+        return Err(rollback_batch(transaction, "sync".into(), mismatch));
+    }
+
+    Ok(Table::new(synced_operations))
+}
@@ -1,14 +1,47 @@
-use crate::config_loader::AppConfig;
+use crate::config_loader::{self, AppConfig};
+use crate::core::install_record::InstallRecords;
 use crate::core::plugin::Plugin;
-use crate::core::registry::{self, Registry};
-use crate::error::Result;
-use crate::fs::paths::get_registry_file_path;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::{get_install_records_file_path, get_trash_folder_path};
+use crate::fs::{trash, visidatarc};
+use crate::utils::get_home_dir;
 use tabled::Table;
 use tracing::info;
 
 pub async fn execute(name: &str) -> Result<Table> {
     info!("Uninstall plugin({})!", name);
-    // TODO disable the plugin
-    // TODO delete the plugin file from plugin_folder
-    Ok(Table::new(Vec::<Plugin>::new()))
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    if !Registry::get_installed_plugins()?.contains(name) {
+        return Err(VDPMError::PluginNotInstalled(name.to_string()));
+    }
+
+    let rc_file_path = get_home_dir().join(&config.settings.rc_file);
+    let was_enabled = visidatarc::disable_plugin(&rc_file_path, name, false).await?;
+
+    let install_records_file_path = get_install_records_file_path()?;
+    let mut install_records = InstallRecords::from_file(&install_records_file_path).await?;
+    let install_record = install_records.plugins.remove(name);
+
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+    let trash_entry_folder = trash::move_to_trash(
+        &get_trash_folder_path()?,
+        &plugin_folder,
+        name,
+        was_enabled,
+        install_record,
+    )?;
+    install_records.to_file(&install_records_file_path).await?;
+    info!(
+        "Plugin({}) is moved to {}, use `vdpm restore {}` to bring it back!",
+        name,
+        trash_entry_folder.display(),
+        name
+    );
+
+    Ok(Table::new(vec![Plugin {
+        name: name.to_string(),
+        ..Default::default()
+    }]))
 }
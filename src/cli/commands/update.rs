@@ -0,0 +1,124 @@
+use crate::cli::commands::install::{install_resolved, record_install, resolve_from_registries};
+use crate::cli::commands::outdated::find_outdated;
+use crate::config_loader::{self, AppConfig};
+use crate::core::install_record::InstallRecords;
+use crate::core::registry::Registry;
+use crate::core::version::is_newer;
+use crate::error::{Result, VDPMError};
+use crate::fs::paths::{get_backups_folder_path, get_install_records_file_path};
+use crate::fs::trash;
+use crate::utils::get_home_dir;
+use tabled::{Table, Tabled};
+use tracing::{error, info};
+
+#[derive(Debug, Tabled)]
+struct UpdatedPlugin {
+    name: String,
+    previous: String,
+    current: String,
+    status: &'static str,
+}
+
+/// Upgrades one plugin in place. The previous files are kept in the backups
+/// folder, so `vdpm rollback` can bring them back, and are put back right away
+/// when the new version fails to install. A plugin already at the latest
+/// version is left untouched.
+async fn update_plugin(config: &AppConfig, name: &str) -> Result<UpdatedPlugin> {
+    let registry: Registry = Registry::generate().await?;
+    let plugin = registry
+        .plugins
+        .get(name)
+        .ok_or_else(|| VDPMError::PluginNotInstalled(name.to_string()))?;
+
+    if plugin.pinned {
+        return Err(VDPMError::PluginPinned(name.to_string()));
+    }
+
+    let install_records_file_path = get_install_records_file_path()?;
+    let install_records = InstallRecords::from_file(&install_records_file_path).await?;
+    let install_record = install_records.plugins.get(name).cloned();
+    let record_registry = install_record
+        .as_ref()
+        .and_then(|record| record.registry.as_deref());
+    let resolved = resolve_from_registries(config, record_registry, name, None).await?;
+    if let Some(version) = &plugin.version
+        && !is_newer(&resolved.version.version, version)
+    {
+        info!("Plugin({}) is already up to date at {}", name, version);
+        return Ok(UpdatedPlugin {
+            name: name.to_string(),
+            previous: version.clone(),
+            current: version.clone(),
+            status: "up to date",
+        });
+    }
+
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+    let backup_folder = trash::move_to_trash(
+        &get_backups_folder_path()?,
+        &plugin_folder,
+        name,
+        plugin.enabled,
+        install_record,
+    )?;
+
+    match install_resolved(config, &resolved, &plugin_folder).await {
+        Ok(installed) => {
+            record_install(&installed, &plugin_folder).await?;
+            info!(
+                "Plugin({}) is updated to {}, previous version is kept in {}",
+                name,
+                resolved.version.version,
+                backup_folder.display()
+            );
+            Ok(UpdatedPlugin {
+                name: name.to_string(),
+                previous: plugin.version.clone().unwrap_or_else(|| "unknown".into()),
+                current: resolved.version.version,
+                status: "updated",
+            })
+        }
+        Err(e) => {
+            error!("Updating plugin({}) failed, restoring it: {}", name, e);
+            trash::restore_trash_entry(&backup_folder, &plugin_folder, name)?;
+            Err(e)
+        }
+    }
+}
+
+pub async fn execute(name: Option<&str>, all: bool) -> Result<Table> {
+    let config: AppConfig = config_loader::load_or_create()?;
+    let names: Vec<String> = match (name, all) {
+        (Some(name), _) => vec![name.to_string()],
+        (None, true) => {
+            let registry: Registry = Registry::generate().await?;
+            find_outdated(&config)
+                .await?
+                .into_iter()
+                .map(|outdated| outdated.name)
+                .filter(|name| {
+                    let pinned = registry
+                        .plugins
+                        .get(name)
+                        .is_some_and(|plugin| plugin.pinned);
+                    if pinned {
+                        info!("Plugin({}) is pinned, skipping it!", name);
+                    }
+                    !pinned
+                })
+                .collect()
+        }
+        (None, false) => {
+            return Err(VDPMError::InvalidArguments(
+                "pass a plugin name or --all".into(),
+            ));
+        }
+    };
+
+    let mut updated = Vec::new();
+    for name in names {
+        info!("Update plugin({})!", name);
+        updated.push(update_plugin(&config, &name).await?);
+    }
+    Ok(Table::new(updated))
+}
@@ -0,0 +1,75 @@
+use crate::config_loader::{self, AppConfig};
+use crate::core::install_record::InstallRecords;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::checksum::{compare_plugin_files, sha256_plugin_files};
+use crate::fs::paths::get_install_records_file_path;
+use crate::utils::get_home_dir;
+use std::collections::BTreeSet;
+use tabled::{Table, Tabled};
+use tracing::{info, warn};
+
+#[derive(Debug, Tabled)]
+struct VerifiedFile {
+    plugin: String,
+    file: String,
+    status: String,
+}
+
+/// Checks the files of one or all installed plugins against the sha256
+/// recorded when they were installed. Plugins vdpm did not install have no
+/// recorded hashes and are reported as untracked.
+pub async fn execute(name: Option<&str>) -> Result<Table> {
+    info!("Verifying plugin files!");
+    let config: AppConfig = config_loader::load_or_create()?;
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+    let install_records = InstallRecords::from_file(&get_install_records_file_path()?).await?;
+
+    let installed_plugins = Registry::get_installed_plugins()?;
+    let plugin_names: BTreeSet<String> = match name {
+        Some(name) if installed_plugins.contains(name) => BTreeSet::from([name.to_string()]),
+        Some(name) => return Err(VDPMError::PluginNotInstalled(name.to_string())),
+        None => installed_plugins.into_iter().collect(),
+    };
+
+    let mut verified_files = Vec::new();
+    for plugin_name in plugin_names {
+        let recorded = match install_records.plugins.get(&plugin_name) {
+            Some(install_record) if !install_record.files.is_empty() => &install_record.files,
+            _ => {
+                verified_files.push(VerifiedFile {
+                    plugin: plugin_name,
+                    file: String::new(),
+                    status: "untracked".into(),
+                });
+                continue;
+            }
+        };
+
+        let current = sha256_plugin_files(&plugin_folder, &plugin_name).map_err(|e| {
+            VDPMError::PluginFileError(
+                format!("Failed to hash files of plugin({})", plugin_name),
+                e,
+            )
+        })?;
+        let changes = compare_plugin_files(recorded, &current);
+        if changes.is_empty() {
+            verified_files.push(VerifiedFile {
+                plugin: plugin_name,
+                file: String::new(),
+                status: "ok".into(),
+            });
+            continue;
+        }
+        for (file, status) in changes {
+            warn!("Plugin({}) file {} is {}", plugin_name, file, status);
+            verified_files.push(VerifiedFile {
+                plugin: plugin_name.clone(),
+                file,
+                status: status.to_string(),
+            });
+        }
+    }
+
+    Ok(Table::new(verified_files))
+}
@@ -1,19 +1,55 @@
 pub mod args;
 pub mod commands;
+use crate::core::lockfile;
+use crate::core::search::SearchQuery;
 use crate::error::Result;
 
 use args::Commands;
 use tabled::Table;
 
+/// Runs the command and keeps `vdpm.lock` in line with the plugins it changed.
 pub async fn run(command: &Commands) -> Result<Table> {
+    let result: Table = dispatch(command).await?;
+    if command.changes_plugins() {
+        lockfile::update().await?;
+    }
+    Ok(result)
+}
+
+/// Runs the command without touching the lock file, `sync` applies its
+/// operations this way so a failed sync leaves the lock file it read intact.
+pub async fn dispatch(command: &Commands) -> Result<Table> {
     // TODO @memedov shall we add trait to these commands?
     let result: Table = match command {
         Commands::List => commands::list::execute().await?,
-        Commands::Enable { name } => commands::enable::execute(&name).await?,
-        Commands::Disable { name } => commands::disable::execute(&name).await?,
-        Commands::Install { name } => commands::install::execute(&name).await?,
-        Commands::Uninstall { name } => commands::uninstall::execute(&name).await?,
-        Commands::Interactive => unreachable!("Interactive mode is handled in main!"),
+        Commands::Enable { name } => commands::enable::execute(name).await?,
+        Commands::Disable { name, comment } => commands::disable::execute(name, *comment).await?,
+        Commands::Install { name } => commands::install::execute(name).await?,
+        Commands::Uninstall { name } => commands::uninstall::execute(name).await?,
+        Commands::Outdated => commands::outdated::execute().await?,
+        Commands::Update { name, all } => commands::update::execute(name.as_deref(), *all).await?,
+        Commands::Rollback { name } => commands::rollback::execute(name).await?,
+        Commands::Restore { name } => commands::restore::execute(name).await?,
+        Commands::Pin { name } => commands::pin::execute(name, true).await?,
+        Commands::Unpin { name } => commands::pin::execute(name, false).await?,
+        Commands::Sync => commands::sync::execute().await?,
+        Commands::Apply { manifest, dry_run } => {
+            commands::apply::execute(manifest, *dry_run).await?
+        }
+        Commands::Verify { name } => commands::verify::execute(name.as_deref()).await?,
+        Commands::Explain { name } => commands::explain::execute(name).await?,
+        Commands::Search { query, tag, author } => {
+            commands::search::execute(SearchQuery {
+                text: query.clone(),
+                tag: tag.clone(),
+                author: author.clone(),
+            })
+            .await?
+        }
+        Commands::Refresh => commands::refresh::execute().await?,
+        Commands::Registry { command } => commands::registry::execute(command).await?,
+        Commands::Interactive { .. } => unreachable!("Interactive mode is handled in main!"),
+        Commands::Rpc | Commands::Serve { .. } => unreachable!("JSON-RPC is served from main!"),
     };
     Ok(result)
 }
@@ -3,10 +3,53 @@ use serde::Deserialize;
 use std::fmt;
 use std::fs::{self};
 use std::path::Path;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, value};
+
+pub const CONFIG_FILE: &str = "config.toml";
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub settings: Settings,
+    #[serde(default)]
+    pub registries: Vec<RegistryConfig>,
+    /// Publisher keys plugin signatures are verified against
+    #[serde(default)]
+    pub trusted_keys: Vec<TrustedKey>,
+}
+
+/// A minisign public key plugins may be signed with, configured as a
+/// `[[trusted_keys]]` entry.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TrustedKey {
+    pub name: String,
+    /// Base64 public key, the second line of a minisign `.pub` file
+    pub public_key: String,
+}
+
+/// A plugin index `vdpm` searches and installs from, configured as a
+/// `[[registries]]` entry.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct RegistryConfig {
+    pub name: String,
+    /// Url or local path of the plugin index, see `core::index`
+    pub url: String,
+    /// Registries with a higher priority are searched first
+    #[serde(default)]
+    pub priority: i64,
+    /// Name of the environment variable holding a bearer token for `url`
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Refuse plugins without a valid signature from a trusted key
+    #[serde(default)]
+    pub require_signature: bool,
+}
+
+impl RegistryConfig {
+    pub fn token(&self) -> Option<String> {
+        self.token_env
+            .as_ref()
+            .and_then(|token_env| std::env::var(token_env).ok())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,15 +77,151 @@ impl fmt::Display for Settings {
 
 impl fmt::Display for AppConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.settings)
+        let registry_names: Vec<&str> = self
+            .registries
+            .iter()
+            .map(|registry| registry.name.as_str())
+            .collect();
+        write!(
+            f,
+            "{}, registries: [{}]",
+            self.settings,
+            registry_names.join(", ")
+        )
+    }
+}
+
+impl AppConfig {
+    /// Registries in the order they are searched.
+    pub fn registries_by_priority(&self) -> Vec<&RegistryConfig> {
+        let mut registries: Vec<&RegistryConfig> = self.registries.iter().collect();
+        registries.sort_by_key(|registry| std::cmp::Reverse(registry.priority));
+        registries
     }
 }
 
 pub fn load_or_create() -> Result<AppConfig> {
     // TODO if the config file is not there, we should create a default one
-    let config_path = Path::new("config.toml");
+    let config_path = Path::new(CONFIG_FILE);
     let config_str = fs::read_to_string(config_path).expect("Failed to read config file");
 
     toml::de::from_str(&config_str)
         .map_err(|e| VDPMError::ConfigError("Failed to parse config".into(), e))
 }
+
+fn read_config_document(config_path: &Path) -> Result<DocumentMut> {
+    let config_str = fs::read_to_string(config_path)
+        .map_err(|e| VDPMError::ConfigWriteError("Failed to read config".into(), e))?;
+    config_str
+        .parse::<DocumentMut>()
+        .map_err(|e| VDPMError::ConfigEditError("Failed to parse config".into(), e))
+}
+
+fn write_config_document(config_path: &Path, document: &DocumentMut) -> Result<()> {
+    fs::write(config_path, document.to_string())
+        .map_err(|e| VDPMError::ConfigWriteError("Failed to write config".into(), e))
+}
+
+/// Appends a `[[registries]]` entry, keeping the rest of the config file as is.
+pub fn add_registry(config_path: &Path, registry: &RegistryConfig) -> Result<()> {
+    let mut document = read_config_document(config_path)?;
+    let registries = document
+        .entry("registries")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| VDPMError::InvalidConfig("registries must be [[registries]]".into()))?;
+
+    if registries
+        .iter()
+        .any(|table| table.get("name").and_then(|name| name.as_str()) == Some(&registry.name))
+    {
+        return Err(VDPMError::RegistryAlreadyExists(registry.name.clone()));
+    }
+
+    let mut table = Table::new();
+    table.insert("name", value(&registry.name));
+    table.insert("url", value(&registry.url));
+    table.insert("priority", value(registry.priority));
+    if let Some(token_env) = &registry.token_env {
+        table.insert("token_env", value(token_env));
+    }
+    if registry.require_signature {
+        table.insert("require_signature", value(true));
+    }
+    registries.push(table);
+
+    write_config_document(config_path, &document)
+}
+
+/// Removes the `[[registries]]` entry named `name`, keeping the rest of the config file as is.
+pub fn remove_registry(config_path: &Path, name: &str) -> Result<()> {
+    let mut document = read_config_document(config_path)?;
+    let registries = document
+        .get_mut("registries")
+        .and_then(|registries| registries.as_array_of_tables_mut())
+        .ok_or_else(|| VDPMError::UnknownRegistry(name.to_string()))?;
+
+    let before = registries.len();
+    registries.retain(|table| table.get("name").and_then(|name| name.as_str()) != Some(name));
+    if registries.len() == before {
+        return Err(VDPMError::UnknownRegistry(name.to_string()));
+    }
+
+    write_config_document(config_path, &document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[settings]
+vdpm_config_folder_path = ".config/vdpm"
+plugin_manager_file = "plugins.csv"
+plugin_folder = ".visidata/plugins"
+
+# where visidata looks for imports
+rc_file = ".visidatarc"
+
+logs_dir = "vdpm_logs"
+"#;
+
+    #[test]
+    fn test_add_and_remove_registries() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(CONFIG_FILE);
+        fs::write(&config_path, CONFIG).unwrap();
+
+        let official = RegistryConfig {
+            name: "official".into(),
+            url: "https://example.com/index.json".into(),
+            priority: 10,
+            token_env: None,
+            require_signature: false,
+        };
+        let internal = RegistryConfig {
+            name: "internal".into(),
+            url: "/srv/plugins/index.json".into(),
+            priority: 20,
+            token_env: Some("INTERNAL_TOKEN".into()),
+            require_signature: true,
+        };
+        add_registry(&config_path, &official).unwrap();
+        add_registry(&config_path, &internal).unwrap();
+        assert!(matches!(
+            add_registry(&config_path, &official),
+            Err(VDPMError::RegistryAlreadyExists(_))
+        ));
+
+        let config_str = fs::read_to_string(&config_path).unwrap();
+        assert!(config_str.starts_with(CONFIG));
+        let config: AppConfig = toml::de::from_str(&config_str).unwrap();
+        assert_eq!(config.registries, vec![official.clone(), internal.clone()]);
+        assert_eq!(config.registries_by_priority(), vec![&internal, &official]);
+
+        remove_registry(&config_path, "internal").unwrap();
+        let config: AppConfig =
+            toml::de::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config.registries, vec![official]);
+        assert!(remove_registry(&config_path, "internal").is_err());
+    }
+}
@@ -0,0 +1,504 @@
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Identifier(String),
+    Str(String),
+    Punct(char),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PluginReport {
+    pub docstring: Option<String>,
+    pub commands: Vec<CommandBinding>,
+    pub options: Vec<OptionDeclaration>,
+    pub api_functions: Vec<ApiFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandBinding {
+    pub sheet: String,
+    pub keystrokes: String,
+    pub longname: String,
+    pub help: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionDeclaration {
+    pub name: String,
+    pub default: String,
+    pub help: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiFunction {
+    pub class: String,
+    pub name: String,
+    pub help: String,
+}
+
+impl PluginReport {
+    pub fn extend(&mut self, other: PluginReport) {
+        if self.docstring.is_none() {
+            self.docstring = other.docstring;
+        }
+        self.commands.extend(other.commands);
+        self.options.extend(other.options);
+        self.api_functions.extend(other.api_functions);
+    }
+}
+
+fn is_string_prefix(prefix: &str) -> bool {
+    prefix.len() <= 2
+        && prefix
+            .chars()
+            .all(|c| matches!(c.to_ascii_lowercase(), 'r' | 'b' | 'u' | 'f'))
+}
+
+fn unescape(content: &str, raw: bool) -> String {
+    if raw {
+        return content.to_string();
+    }
+    let mut unescaped = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('\n') => {}
+            Some(escaped @ ('\\' | '\'' | '"')) => unescaped.push(escaped),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Reads the string literal starting at `start` (just after its prefix) and
+/// returns its value and the offset after the closing quote.
+fn read_string(source: &str, start: usize, raw: bool) -> (String, usize) {
+    let rest = &source[start..];
+    let quote = &rest[..1];
+    let delimiter = if rest.starts_with(&quote.repeat(3)) {
+        quote.repeat(3)
+    } else {
+        quote.to_string()
+    };
+
+    let content_start = start + delimiter.len();
+    let mut offset = content_start;
+    while offset < source.len() {
+        let rest = &source[offset..];
+        if let Some(escaped) = rest.strip_prefix('\\') {
+            offset += 1 + escaped.chars().next().map_or(0, char::len_utf8);
+        } else if rest.starts_with(&delimiter) {
+            return (
+                unescape(&source[content_start..offset], raw),
+                offset + delimiter.len(),
+            );
+        } else if delimiter.len() == 1 && rest.starts_with('\n') {
+            break;
+        } else {
+            offset += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    (unescape(&source[content_start..offset], raw), offset)
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+    while let Some(c) = source[offset..].chars().next() {
+        let start = offset;
+        if c.is_whitespace() {
+            offset += c.len_utf8();
+        } else if c == '#' {
+            offset = source[offset..]
+                .find('\n')
+                .map_or(source.len(), |newline| offset + newline);
+        } else if c == '\'' || c == '"' {
+            let (value, end) = read_string(source, offset, false);
+            offset = end;
+            tokens.push(Token {
+                kind: TokenKind::Str(value),
+                start,
+                end,
+            });
+        } else if c.is_alphanumeric() || c == '_' {
+            let length = source[offset..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(source.len() - offset);
+            let word = &source[offset..offset + length];
+            offset += length;
+            if is_string_prefix(word) && source[offset..].starts_with(['\'', '"']) {
+                let raw = word.to_ascii_lowercase().contains('r');
+                let (value, end) = read_string(source, offset, raw);
+                offset = end;
+                tokens.push(Token {
+                    kind: TokenKind::Str(value),
+                    start,
+                    end,
+                });
+            } else {
+                tokens.push(Token {
+                    kind: TokenKind::Identifier(word.to_string()),
+                    start,
+                    end: offset,
+                });
+            }
+        } else {
+            offset += c.len_utf8();
+            tokens.push(Token {
+                kind: TokenKind::Punct(c),
+                start,
+                end: offset,
+            });
+        }
+    }
+    tokens
+}
+
+/// Removes the indentation docstrings carry over from the source, like
+/// python's `inspect.cleandoc`.
+fn clean_docstring(docstring: &str) -> String {
+    let lines: Vec<&str> = docstring.lines().collect();
+    let indentation = lines
+        .iter()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                line.trim()
+            } else {
+                line.get(indentation..).unwrap_or("").trim_end()
+            }
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn is_identifier(token: Option<&Token>, name: &str) -> bool {
+    matches!(token, Some(Token { kind: TokenKind::Identifier(identifier), .. }) if identifier == name)
+}
+
+fn is_punct(token: Option<&Token>, punct: char) -> bool {
+    matches!(token, Some(Token { kind: TokenKind::Punct(c), .. }) if *c == punct)
+}
+
+fn identifier(token: Option<&Token>) -> Option<&str> {
+    match token {
+        Some(Token {
+            kind: TokenKind::Identifier(identifier),
+            ..
+        }) => Some(identifier),
+        _ => None,
+    }
+}
+
+/// A call argument: the keyword if given, and its value as a string for
+/// (implicitly concatenated) string literals or as source text otherwise.
+struct Argument {
+    keyword: Option<String>,
+    value: String,
+}
+
+/// Parses the arguments of the call whose `(` is at `open`, returning them
+/// and the index of the token after the closing `)`.
+fn parse_arguments(source: &str, tokens: &[Token], open: usize) -> (Vec<Argument>, usize) {
+    let mut arguments: Vec<Vec<&Token>> = vec![Vec::new()];
+    let mut depth = 0;
+    let mut index = open + 1;
+    while let Some(token) = tokens.get(index) {
+        index += 1;
+        match token.kind {
+            TokenKind::Punct('(' | '[' | '{') => depth += 1,
+            TokenKind::Punct(')') if depth == 0 => break,
+            TokenKind::Punct(')' | ']' | '}') => depth -= 1,
+            TokenKind::Punct(',') if depth == 0 => {
+                arguments.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(argument) = arguments.last_mut() {
+            argument.push(token);
+        }
+    }
+
+    let arguments = arguments
+        .into_iter()
+        .filter(|argument| !argument.is_empty())
+        .map(|mut argument| {
+            let keyword = match argument.as_slice() {
+                [
+                    Token {
+                        kind: TokenKind::Identifier(keyword),
+                        ..
+                    },
+                    Token {
+                        kind: TokenKind::Punct('='),
+                        ..
+                    },
+                    _,
+                    ..,
+                ] => Some(keyword.clone()),
+                _ => None,
+            };
+            if keyword.is_some() {
+                argument.drain(..2);
+            }
+
+            let strings: Option<Vec<&str>> = argument
+                .iter()
+                .map(|token| match &token.kind {
+                    TokenKind::Str(value) => Some(value.as_str()),
+                    _ => None,
+                })
+                .collect();
+            let value = match strings {
+                Some(strings) => strings.concat(),
+                None => {
+                    let (start, end) = (argument[0].start, argument[argument.len() - 1].end);
+                    source[start..end].to_string()
+                }
+            };
+            Argument { keyword, value }
+        })
+        .collect();
+    (arguments, index)
+}
+
+/// Picks the argument passed by `keyword` or at `position`.
+fn argument(arguments: &[Argument], position: usize, keyword: &str) -> String {
+    arguments
+        .iter()
+        .find(|argument| argument.keyword.as_deref() == Some(keyword))
+        .or_else(|| {
+            arguments
+                .iter()
+                .filter(|argument| argument.keyword.is_none())
+                .nth(position)
+        })
+        .map(|argument| argument.value.clone())
+        .unwrap_or_default()
+}
+
+/// Returns the docstring of the function whose parameter list opens at `open`.
+fn function_docstring(source: &str, tokens: &[Token], open: usize) -> String {
+    let (_, mut index) = parse_arguments(source, tokens, open);
+    while let Some(token) = tokens.get(index) {
+        index += 1;
+        if token.kind == TokenKind::Punct(':') {
+            break;
+        }
+    }
+    match tokens.get(index) {
+        Some(Token {
+            kind: TokenKind::Str(docstring),
+            ..
+        }) => clean_docstring(docstring),
+        _ => String::new(),
+    }
+}
+
+/// Statically extracts what a plugin does from its python source, without
+/// executing it: the module docstring, `addCommand` keybindings, option
+/// declarations and `@<Class>.api` functions.
+pub fn explain(source: &str) -> PluginReport {
+    let tokens = tokenize(source);
+    let mut report = PluginReport {
+        docstring: match tokens.first() {
+            Some(Token {
+                kind: TokenKind::Str(docstring),
+                ..
+            }) => Some(clean_docstring(docstring)),
+            _ => None,
+        },
+        ..Default::default()
+    };
+
+    for (index, token) in tokens.iter().enumerate() {
+        let receiver = if index >= 2 && is_punct(tokens.get(index - 1), '.') {
+            identifier(tokens.get(index - 2))
+        } else {
+            None
+        };
+        let is_definition = index >= 1 && is_identifier(tokens.get(index - 1), "def");
+
+        match &token.kind {
+            TokenKind::Identifier(name)
+                if name == "addCommand" && is_punct(tokens.get(index + 1), '(') =>
+            {
+                let (arguments, _) = parse_arguments(source, &tokens, index + 1);
+                report.commands.push(CommandBinding {
+                    sheet: receiver.unwrap_or("BaseSheet").to_string(),
+                    keystrokes: argument(&arguments, 0, "keystrokes"),
+                    longname: argument(&arguments, 1, "longname"),
+                    help: argument(&arguments, 3, "helpstr"),
+                });
+            }
+            TokenKind::Identifier(name)
+                if (name == "option" || name == "theme_option")
+                    && !is_definition
+                    && receiver.is_none_or(|receiver| receiver == "vd")
+                    && is_punct(tokens.get(index + 1), '(') =>
+            {
+                let (arguments, _) = parse_arguments(source, &tokens, index + 1);
+                report.options.push(OptionDeclaration {
+                    name: argument(&arguments, 0, "name"),
+                    default: argument(&arguments, 1, "default"),
+                    help: argument(&arguments, 2, "helpstr"),
+                });
+            }
+            TokenKind::Punct('@')
+                if is_punct(tokens.get(index + 2), '.')
+                    && is_identifier(tokens.get(index + 3), "api") =>
+            {
+                let class = identifier(tokens.get(index + 1)).unwrap_or_default();
+                let definition = tokens[index + 4..]
+                    .iter()
+                    .position(|token| token.kind == TokenKind::Identifier("def".into()))
+                    .map(|position| index + 4 + position);
+                if let Some(definition) = definition
+                    && let Some(name) = identifier(tokens.get(definition + 1))
+                {
+                    report.api_functions.push(ApiFunction {
+                        class: class.to_string(),
+                        name: name.to_string(),
+                        help: function_docstring(source, &tokens, definition + 2),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    report
+}
+
+/// Reads the module level `__version__ = "..."` assignment, if any.
+pub fn module_version(source: &str) -> Option<String> {
+    tokenize(source).windows(3).find_map(|window| match window {
+        [
+            Token {
+                kind: TokenKind::Identifier(identifier),
+                ..
+            },
+            Token {
+                kind: TokenKind::Punct('='),
+                ..
+            },
+            Token {
+                kind: TokenKind::Str(version),
+                ..
+            },
+        ] if identifier == "__version__" => Some(version.clone()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLUGIN: &str = r#"#!/usr/bin/env python3
+'''
+Adds a frequency table of the current column.
+
+    Usage: press z^F
+'''
+
+from visidata import vd, VisiData, Sheet, BaseSheet
+
+__version__ = '0.3.1'
+
+vd.option('freq_limit', 100, 'max rows in the frequency table')
+vd.option(name="freq_color", default="green",
+          helpstr="color " 'of the bars')
+
+@VisiData.api
+def open_freq(vd, sheet, limit=options.freq_limit):
+    """Open the frequency table
+       of `sheet`."""
+    return FreqSheet(sheet)
+
+def option(self):  # not a declaration
+    pass
+
+Sheet.addCommand('z^F', 'open-freq', 'vd.open_freq(sheet, limit=(1, 2))', 'open frequency table')
+BaseSheet.addCommand(None, 'freq-reset', "vd.status('reset # not a comment')",
+    helpstr=r"reset \n counts")
+"#;
+
+    #[test]
+    fn test_explain_plugin_source() {
+        let report = explain(PLUGIN);
+        assert_eq!(module_version(PLUGIN).as_deref(), Some("0.3.1"));
+
+        assert_eq!(
+            report.docstring.as_deref(),
+            Some("Adds a frequency table of the current column.\n\n    Usage: press z^F")
+        );
+        assert_eq!(
+            report.options,
+            vec![
+                OptionDeclaration {
+                    name: "freq_limit".into(),
+                    default: "100".into(),
+                    help: "max rows in the frequency table".into(),
+                },
+                OptionDeclaration {
+                    name: "freq_color".into(),
+                    default: "green".into(),
+                    help: "color of the bars".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            report.api_functions,
+            vec![ApiFunction {
+                class: "VisiData".into(),
+                name: "open_freq".into(),
+                help: "Open the frequency table\nof `sheet`.".into(),
+            }]
+        );
+        assert_eq!(
+            report.commands,
+            vec![
+                CommandBinding {
+                    sheet: "Sheet".into(),
+                    keystrokes: "z^F".into(),
+                    longname: "open-freq".into(),
+                    help: "open frequency table".into(),
+                },
+                CommandBinding {
+                    sheet: "BaseSheet".into(),
+                    keystrokes: "None".into(),
+                    longname: "freq-reset".into(),
+                    help: "reset \\n counts".into(),
+                },
+            ]
+        );
+    }
+}
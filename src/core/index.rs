@@ -0,0 +1,344 @@
+use crate::config_loader::RegistryConfig;
+use crate::core::version::{VersionReq, compare_versions};
+use crate::error::{Result, VDPMError};
+use crate::sources::http;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// The newest index schema this vdpm understands. Indexes declaring a newer
+/// `schema_version` are rejected instead of being half understood.
+pub const SUPPORTED_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginIndex {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub plugins: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub versions: Vec<IndexVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexVersion {
+    pub version: String,
+    pub url: String,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Url or path of a detached minisign signature, `<url>.minisig` if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub min_visidata_version: Option<String>,
+}
+
+impl IndexEntry {
+    /// The last listed version is the latest one.
+    pub fn latest_version(&self) -> Option<&IndexVersion> {
+        self.versions.last()
+    }
+}
+
+impl PluginIndex {
+    pub fn parse(content: &str) -> Result<Self> {
+        let index: PluginIndex = serde_json::from_str(content)
+            .map_err(|e| VDPMError::IndexError("Failed to parse plugin index".into(), e))?;
+
+        if index.schema_version > SUPPORTED_SCHEMA_VERSION {
+            return Err(VDPMError::UnsupportedIndexSchema(
+                index.schema_version,
+                SUPPORTED_SCHEMA_VERSION,
+            ));
+        }
+        Ok(index)
+    }
+
+    pub fn get(&self, plugin_name: &str) -> Option<&IndexEntry> {
+        self.plugins.iter().find(|entry| entry.name == plugin_name)
+    }
+
+    /// Reads the index from an `http(s)://` url, a `file://` url or a local path.
+    pub async fn fetch(location: &str, token: Option<&str>) -> Result<Self> {
+        let content = http::fetch_location(location, token).await?;
+        PluginIndex::parse(&String::from_utf8_lossy(&content))
+    }
+
+    /// Returns the cached index, or `None` when it was never fetched.
+    pub async fn from_cache(path: &Path) -> Result<Option<Self>> {
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => Ok(Some(PluginIndex::parse(&content)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(VDPMError::PluginFileError(
+                format!("Failed to read cached plugin index {}", path.display()),
+                e,
+            )),
+        }
+    }
+
+    pub async fn to_cache(&self, path: &Path) -> Result<&Self> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| VDPMError::IndexError("Failed to serialize plugin index".into(), e))?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                VDPMError::PluginFileError("Failed to create index cache folder".into(), e)
+            })?;
+        }
+        tokio::fs::write(path, content).await.map_err(|e| {
+            VDPMError::PluginFileError(
+                format!("Failed to cache plugin index {}", path.display()),
+                e,
+            )
+        })?;
+        Ok(self)
+    }
+}
+
+pub fn cache_file_path(index_cache_folder: &Path, registry_name: &str) -> PathBuf {
+    index_cache_folder.join(format!("{}.json", registry_name))
+}
+
+/// An index entry together with the registry it was found in.
+#[derive(Debug, Clone)]
+pub struct ResolvedPlugin {
+    pub registry: String,
+    pub entry: IndexEntry,
+    pub version: IndexVersion,
+}
+
+/// Loads the cached indexes of `registries`, skipping registries that were
+/// never refreshed. The order of `registries` is kept.
+pub async fn load_cached_indexes(
+    registries: &[&RegistryConfig],
+    index_cache_folder: &Path,
+) -> Result<Vec<(String, PluginIndex)>> {
+    let mut indexes = Vec::new();
+    for registry in registries {
+        match PluginIndex::from_cache(&cache_file_path(index_cache_folder, &registry.name)).await? {
+            Some(index) => indexes.push((registry.name.clone(), index)),
+            None => warn!("Plugin index of registry({}) is not cached!", registry.name),
+        }
+    }
+    Ok(indexes)
+}
+
+/// Walks the cached indexes of `registries` in order and returns the first
+/// one listing the plugin, at `version` or at its latest version.
+pub async fn resolve(
+    registries: &[&RegistryConfig],
+    index_cache_folder: &Path,
+    plugin_name: &str,
+    version: Option<&str>,
+) -> Result<ResolvedPlugin> {
+    let not_found = match version {
+        Some(version) => format!("{}@{}", plugin_name, version),
+        None => plugin_name.to_string(),
+    };
+    resolve_with(
+        registries,
+        index_cache_folder,
+        plugin_name,
+        |entry| match version {
+            Some(version) => entry
+                .versions
+                .iter()
+                .find(|index_version| index_version.version == version),
+            None => entry.latest_version(),
+        },
+        not_found,
+    )
+    .await
+}
+
+/// Like [`resolve`], but picks the highest version satisfying `version_req`.
+pub async fn resolve_matching(
+    registries: &[&RegistryConfig],
+    index_cache_folder: &Path,
+    plugin_name: &str,
+    version_req: &VersionReq,
+) -> Result<ResolvedPlugin> {
+    resolve_with(
+        registries,
+        index_cache_folder,
+        plugin_name,
+        |entry| {
+            entry
+                .versions
+                .iter()
+                .filter(|index_version| version_req.matches(&index_version.version))
+                .max_by(|left, right| compare_versions(&left.version, &right.version))
+        },
+        format!("{}@{}", plugin_name, version_req),
+    )
+    .await
+}
+
+async fn resolve_with(
+    registries: &[&RegistryConfig],
+    index_cache_folder: &Path,
+    plugin_name: &str,
+    select_version: impl Fn(&IndexEntry) -> Option<&IndexVersion>,
+    not_found: String,
+) -> Result<ResolvedPlugin> {
+    let indexes = load_cached_indexes(registries, index_cache_folder).await?;
+    if indexes.is_empty() {
+        let registry_names: Vec<&str> = registries
+            .iter()
+            .map(|registry| registry.name.as_str())
+            .collect();
+        return Err(VDPMError::IndexNotCached(registry_names.join(", ")));
+    }
+
+    indexes
+        .into_iter()
+        .find_map(|(registry, index)| {
+            let entry = index.get(plugin_name)?;
+            let version = select_version(entry)?.clone();
+            Some(ResolvedPlugin {
+                registry,
+                entry: entry.clone(),
+                version,
+            })
+        })
+        .ok_or(VDPMError::PluginNotFound(not_found))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = r#"{
+        "schema_version": 1,
+        "plugins": [
+            {
+                "name": "hello",
+                "description": "Says hello",
+                "author": "vdpm",
+                "tags": ["demo"],
+                "versions": [
+                    { "version": "0.1.0", "url": "https://example.com/hello-0.1.0.py" },
+                    {
+                        "version": "0.2.0",
+                        "url": "https://example.com/hello-0.2.0.py",
+                        "sha256": "abc",
+                        "min_visidata_version": "3.0"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_fetch_local_index_and_cache_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = dir.path().join("index.json");
+        std::fs::write(&index_path, INDEX).unwrap();
+
+        let index = PluginIndex::fetch(index_path.to_str().unwrap(), None)
+            .await
+            .unwrap();
+        let hello = index.get("hello").unwrap();
+        assert_eq!(hello.latest_version().unwrap().version, "0.2.0");
+        assert_eq!(
+            hello.latest_version().unwrap().min_visidata_version,
+            Some("3.0".to_string())
+        );
+
+        let cache_path = cache_file_path(&dir.path().join("cache"), "default");
+        assert!(
+            PluginIndex::from_cache(&cache_path)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        index.to_cache(&cache_path).await.unwrap();
+        assert_eq!(
+            PluginIndex::from_cache(&cache_path).await.unwrap(),
+            Some(index)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_walks_registries_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut fallback = PluginIndex::parse(INDEX).unwrap();
+        fallback.plugins[0].versions.push(IndexVersion {
+            version: "0.3.0".into(),
+            url: "https://example.com/hello-0.3.0.py".into(),
+            sha256: None,
+            signature: None,
+            min_visidata_version: None,
+        });
+        PluginIndex::parse(INDEX)
+            .unwrap()
+            .to_cache(&cache_file_path(dir.path(), "preferred"))
+            .await
+            .unwrap();
+        fallback
+            .to_cache(&cache_file_path(dir.path(), "fallback"))
+            .await
+            .unwrap();
+
+        let registry = |name: &str| RegistryConfig {
+            name: name.into(),
+            url: String::new(),
+            priority: 0,
+            token_env: None,
+            require_signature: false,
+        };
+        let (preferred, fallback, missing) = (
+            registry("preferred"),
+            registry("fallback"),
+            registry("missing"),
+        );
+
+        let resolved = resolve(
+            &[&missing, &preferred, &fallback],
+            dir.path(),
+            "hello",
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resolved.registry, "preferred");
+        assert_eq!(resolved.version.version, "0.2.0");
+
+        let resolved = resolve(&[&preferred, &fallback], dir.path(), "hello", Some("0.3.0"))
+            .await
+            .unwrap();
+        assert_eq!(resolved.registry, "fallback");
+
+        let resolved = resolve_matching(
+            &[&preferred, &fallback],
+            dir.path(),
+            "hello",
+            &VersionReq::parse(">0.1").unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resolved.version.version, "0.2.0");
+
+        assert!(matches!(
+            resolve(&[&missing], dir.path(), "hello", None).await,
+            Err(VDPMError::IndexNotCached(_))
+        ));
+    }
+
+    #[test]
+    fn test_newer_schema_is_rejected() {
+        assert!(matches!(
+            PluginIndex::parse(r#"{ "schema_version": 2, "plugins": [] }"#),
+            Err(VDPMError::UnsupportedIndexSchema(2, 1))
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::error::{RegistryError, Result, VDPMError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// What vdpm remembers about a plugin it installed, which can not be derived
+/// from the plugin folder and .visidatarc alone.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstallRecord {
+    pub source: String,
+    pub installed_at: String,
+    #[serde(default)]
+    pub commit: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// The registry the plugin was resolved from, updates are looked up there
+    #[serde(default)]
+    pub registry: Option<String>,
+    /// The sha256 of every installed file, keyed by its path relative to the
+    /// plugin folder, checked by `vdpm verify`
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    /// The trusted key the plugin was signed with
+    #[serde(default)]
+    pub signed_by: Option<String>,
+    /// Set by `vdpm pin`, `vdpm update` keeps the plugin at its version
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallRecords {
+    pub plugins: BTreeMap<String, InstallRecord>,
+}
+
+impl InstallRecords {
+    pub async fn from_file(path: &Path) -> Result<Self> {
+        let content = match tokio::fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(InstallRecords::default()),
+            Err(e) => {
+                return Err(VDPMError::RegistryOperationError(
+                    "Failed to read install records".into(),
+                    RegistryError::from(e),
+                ));
+            }
+        };
+
+        serde_json::from_str(&content).map_err(|e| {
+            VDPMError::RegistryOperationError(
+                "Failed to parse install records".into(),
+                RegistryError::from(e),
+            )
+        })
+    }
+
+    pub async fn to_file(&self, path: &Path) -> Result<&Self> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| {
+            VDPMError::RegistryOperationError(
+                "Failed to serialize install records".into(),
+                RegistryError::from(e),
+            )
+        })?;
+
+        tokio::fs::write(path, content).await.map_err(|e| {
+            VDPMError::RegistryOperationError(
+                "Failed to write install records".into(),
+                RegistryError::from(e),
+            )
+        })?;
+
+        Ok(self)
+    }
+}
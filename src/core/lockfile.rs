@@ -0,0 +1,200 @@
+use crate::config_loader::{self, AppConfig};
+use crate::core::install_record::{InstallRecord, InstallRecords};
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+use crate::error::{Result, VDPMError};
+use crate::fs::checksum::sha256_plugin;
+use crate::fs::paths::{get_install_records_file_path, get_lock_file_path};
+use crate::utils::get_home_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::debug;
+
+pub const SUPPORTED_LOCK_FILE_VERSION: u32 = 1;
+
+/// The exact plugin set of a machine, written to `vdpm.lock` after every
+/// change so it can be checked in and reproduced with `vdpm sync`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockFile {
+    pub version: u32,
+    #[serde(default)]
+    pub plugins: Vec<LockedPlugin>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockedPlugin {
+    pub name: String,
+    /// Install argument reproducing the plugin, `None` for plugins vdpm did not install
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    pub enabled: bool,
+}
+
+/// Plugins resolved from a registry are locked by name and version rather than
+/// by download url, so `sync` installs them through the same registry again.
+pub(crate) fn locked_source(plugin_name: &str, install_record: &InstallRecord) -> String {
+    match (&install_record.registry, &install_record.version) {
+        (Some(registry), Some(version)) => format!("{}/{}@{}", registry, plugin_name, version),
+        _ => install_record.source.clone(),
+    }
+}
+
+impl LockFile {
+    pub fn from_state(
+        registry: &Registry,
+        install_records: &InstallRecords,
+        plugin_folder: &Path,
+    ) -> Self {
+        let plugins = registry
+            .plugins
+            .values()
+            .map(|plugin| LockedPlugin {
+                name: plugin.name.clone(),
+                source: install_records
+                    .plugins
+                    .get(&plugin.name)
+                    .map(|install_record| locked_source(&plugin.name, install_record)),
+                version: plugin.version.clone(),
+                commit: plugin.commit.clone(),
+                sha256: sha256_plugin(plugin_folder, &plugin.name).ok().flatten(),
+                enabled: plugin.enabled,
+            })
+            .collect();
+
+        LockFile {
+            version: SUPPORTED_LOCK_FILE_VERSION,
+            plugins,
+        }
+    }
+
+    /// Locks the plugins currently installed.
+    pub async fn generate() -> Result<Self> {
+        let config: AppConfig = config_loader::load_or_create()?;
+        let registry: Registry = Registry::generate().await?;
+        let install_records = InstallRecords::from_file(&get_install_records_file_path()?).await?;
+        Ok(LockFile::from_state(
+            &registry,
+            &install_records,
+            &get_home_dir().join(&config.settings.plugin_folder),
+        ))
+    }
+
+    /// The locked plugins as the registry `sync` has to converge to.
+    pub fn to_registry(&self) -> Registry {
+        let plugins: BTreeMap<String, Plugin> = self
+            .plugins
+            .iter()
+            .map(|locked_plugin| {
+                (
+                    locked_plugin.name.clone(),
+                    Plugin {
+                        name: locked_plugin.name.clone(),
+                        enabled: locked_plugin.enabled,
+                        installed: true,
+                        version: locked_plugin.version.clone(),
+                        source: locked_plugin.source.clone(),
+                        commit: locked_plugin.commit.clone(),
+                        sha256: locked_plugin.sha256.clone(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        Registry { plugins }
+    }
+
+    pub fn get(&self, plugin_name: &str) -> Option<&LockedPlugin> {
+        self.plugins
+            .iter()
+            .find(|locked_plugin| locked_plugin.name == plugin_name)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let lock_file: LockFile = toml::from_str(content)
+            .map_err(|e| VDPMError::LockFileParseError("Failed to parse lock file".into(), e))?;
+        if lock_file.version > SUPPORTED_LOCK_FILE_VERSION {
+            return Err(VDPMError::UnsupportedLockFileVersion(
+                lock_file.version,
+                SUPPORTED_LOCK_FILE_VERSION,
+            ));
+        }
+        Ok(lock_file)
+    }
+
+    pub async fn from_file(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+            VDPMError::LockFileError(format!("Failed to read {}", path.display()), e)
+        })?;
+        LockFile::parse(&content)
+    }
+
+    pub async fn to_file(&self, path: &Path) -> Result<&Self> {
+        let content = toml::to_string_pretty(self).map_err(|e| {
+            VDPMError::LockFileSerializeError("Failed to serialize lock file".into(), e)
+        })?;
+        tokio::fs::write(path, content).await.map_err(|e| {
+            VDPMError::LockFileError(format!("Failed to write {}", path.display()), e)
+        })?;
+        Ok(self)
+    }
+}
+
+/// Rewrites `vdpm.lock` from the plugins currently installed.
+pub async fn update() -> Result<()> {
+    let lock_file_path = get_lock_file_path()?;
+    LockFile::generate().await?.to_file(&lock_file_path).await?;
+    debug!("Lock file {} is updated", lock_file_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::{plugin, registry};
+    use std::fs;
+
+    #[test]
+    fn test_lock_file_round_trip() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        fs::write(plugin_folder.path().join("hello.py"), "print(1)").unwrap();
+
+        let registry = registry([Plugin {
+            version: Some("0.2.0".into()),
+            source: Some("https://example.com/hello-0.2.0.py".into()),
+            ..plugin("hello", true)
+        }]);
+        let mut install_records = InstallRecords::default();
+        install_records.plugins.insert(
+            "hello".into(),
+            InstallRecord {
+                source: "https://example.com/hello-0.2.0.py".into(),
+                installed_at: "2026-01-01T00:00:00+00:00".into(),
+                commit: None,
+                version: Some("0.2.0".into()),
+                registry: Some("main".into()),
+                files: BTreeMap::new(),
+                signed_by: None,
+                pinned: false,
+            },
+        );
+
+        let lock_file = LockFile::from_state(&registry, &install_records, plugin_folder.path());
+        let locked_plugin = lock_file.get("hello").unwrap();
+        assert_eq!(locked_plugin.source.as_deref(), Some("main/hello@0.2.0"));
+        assert!(locked_plugin.sha256.is_some());
+
+        let content = toml::to_string_pretty(&lock_file).unwrap();
+        assert_eq!(LockFile::parse(&content).unwrap(), lock_file);
+        assert!(matches!(
+            LockFile::parse("version = 2"),
+            Err(VDPMError::UnsupportedLockFileVersion(2, 1))
+        ));
+    }
+}
@@ -0,0 +1,103 @@
+use crate::core::version::VersionReq;
+use crate::error::{Result, VDPMError};
+use serde::Deserialize;
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "vdpm.toml";
+
+/// The plugins a user wants, declared in `vdpm.toml` and converged to with
+/// `vdpm apply`. Installed plugins missing from the manifest are uninstalled.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Manifest {
+    #[serde(default)]
+    pub plugins: Vec<ManifestPlugin>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ManifestPlugin {
+    pub name: String,
+    /// Version constraint like `^0.2` or `>=1.0, <2`, checked against registry versions
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Anything `vdpm install` accepts, the registries are searched by name if missing
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ManifestPlugin {
+    pub fn version_req(&self) -> Result<Option<VersionReq>> {
+        self.version.as_deref().map(VersionReq::parse).transpose()
+    }
+}
+
+impl Manifest {
+    pub fn parse(content: &str) -> Result<Self> {
+        let manifest: Manifest = toml::from_str(content)
+            .map_err(|e| VDPMError::ManifestParseError("Failed to parse manifest".into(), e))?;
+
+        for (i, plugin) in manifest.plugins.iter().enumerate() {
+            if manifest.plugins[..i]
+                .iter()
+                .any(|other_plugin| other_plugin.name == plugin.name)
+            {
+                return Err(VDPMError::InvalidArguments(format!(
+                    "plugin({}) is declared more than once in the manifest",
+                    plugin.name
+                )));
+            }
+            plugin.version_req()?;
+        }
+        Ok(manifest)
+    }
+
+    pub async fn from_file(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+            VDPMError::ManifestError(format!("Failed to read {}", path.display()), e)
+        })?;
+        Manifest::parse(&content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = Manifest::parse(
+            r#"
+            [[plugins]]
+            name = "hello"
+            version = "^0.2"
+
+            [[plugins]]
+            name = "internal_tools"
+            source = "git+https://example.com/internal_tools.git@v1"
+            enabled = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(manifest.plugins.len(), 2);
+        assert!(manifest.plugins[0].enabled);
+        assert!(
+            manifest.plugins[0]
+                .version_req()
+                .unwrap()
+                .unwrap()
+                .matches("0.2.3")
+        );
+        assert!(!manifest.plugins[1].enabled);
+
+        assert!(Manifest::parse("[[plugins]]\nname = \"a\"\n[[plugins]]\nname = \"a\"").is_err());
+        assert!(matches!(
+            Manifest::parse("[[plugins]]\nname = \"a\"\nversion = \"newest\""),
+            Err(VDPMError::InvalidVersionConstraint(_))
+        ));
+    }
+}
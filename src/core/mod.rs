@@ -1,2 +1,11 @@
+pub mod explain;
+pub mod index;
+pub mod install_record;
+pub mod lockfile;
+pub mod manifest;
 pub mod plugin;
 pub mod registry;
+pub mod search;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
+pub mod version;
@@ -1,9 +1,109 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use tabled::Tabled;
+use tabled::derive::display;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Tabled, Hash)]
+/// What a user asks for in the `action` column of the interactive sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Install,
+    Update,
+    Uninstall,
+    Pin,
+    Unpin,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Install => write!(f, "install"),
+            Action::Update => write!(f, "update"),
+            Action::Uninstall => write!(f, "uninstall"),
+            Action::Pin => write!(f, "pin"),
+            Action::Unpin => write!(f, "unpin"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Tabled)]
+#[tabled(display(Option, "display::option", ""))]
 pub struct Plugin {
     pub name: String,
+    /// Runs once when the interactive sheet is saved, then is cleared again
+    #[serde(default)]
+    #[tabled(skip)]
+    pub action: Option<Action>,
     pub enabled: bool,
     pub installed: bool,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// Kept at its version by `vdpm update --all`
+    #[serde(default)]
+    pub pinned: bool,
+    /// The trusted key the plugin was signed with
+    #[serde(default)]
+    #[tabled(rename = "signed by")]
+    pub signed_by: Option<String>,
+    /// First line of the module docstring
+    #[serde(default)]
+    #[tabled(skip)]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[tabled(skip)]
+    pub installed_at: Option<String>,
+    /// Total bytes of the plugin files
+    #[serde(default)]
+    #[tabled(skip)]
+    pub size: Option<u64>,
+    /// Digest of the plugin files, see [`crate::fs::checksum::sha256_plugin`]
+    #[serde(default)]
+    #[tabled(skip)]
+    pub sha256: Option<String>,
+    /// Why the last interactive edit of the plugin was not applied
+    #[serde(default)]
+    #[tabled(skip)]
+    pub error: Option<String>,
+}
+
+impl Plugin {
+    /// The plugin with the columns of the sheet that only describe the
+    /// installed plugin taken from `installed`, so editing them changes
+    /// nothing. They are cleared for a plugin that is not installed. A pinned
+    /// plugin keeps its `version`, `source` and `commit` as well.
+    pub fn with_read_only_of(&self, installed: Option<&Plugin>) -> Plugin {
+        match installed {
+            Some(installed) if installed.pinned => Plugin {
+                name: self.name.clone(),
+                action: self.action,
+                enabled: self.enabled,
+                error: self.error.clone(),
+                ..installed.clone()
+            },
+            Some(installed) => Plugin {
+                name: self.name.clone(),
+                action: self.action,
+                enabled: self.enabled,
+                version: self.version.clone(),
+                source: self.source.clone(),
+                commit: self.commit.clone(),
+                error: self.error.clone(),
+                ..installed.clone()
+            },
+            None => Plugin {
+                installed: false,
+                pinned: false,
+                signed_by: None,
+                description: None,
+                installed_at: None,
+                size: None,
+                sha256: None,
+                ..self.clone()
+            },
+        }
+    }
 }
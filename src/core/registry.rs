@@ -1,8 +1,14 @@
 use crate::config_loader::{self, AppConfig};
-use crate::core::plugin::{self, Plugin};
-use crate::core::registry;
+use crate::core::explain;
+use crate::core::install_record::{InstallRecord, InstallRecords};
+use crate::core::lockfile::locked_source;
+use crate::core::plugin::Plugin;
 use crate::error::{RegistryError, Result, VDPMError};
-use crate::fs::operations::list_files_by_extension;
+use crate::fs::checksum::{plugin_size, sha256_plugin};
+use crate::fs::operations::{list_files_by_extension, list_package_dirs, plugin_entry_file};
+use crate::fs::paths::get_install_records_file_path;
+use crate::fs::visidatarc;
+use crate::interactive::companion::COMPANION_PLUGIN;
 use crate::utils::get_home_dir;
 use csv::WriterBuilder;
 use serde::{Deserialize, Serialize};
@@ -12,7 +18,7 @@ use tokio;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Registry {
     pub plugins: BTreeMap<String, Plugin>,
 }
@@ -40,12 +46,43 @@ impl Registry {
                 )
             })?
             .into_iter()
+            // rows without a name only carry errors, see `with_error`
+            .filter(|plugin| !plugin.name.is_empty())
             .map(|plugin| (plugin.name.clone(), plugin))
             .collect();
 
         Ok(Registry { plugins })
     }
 
+    /// The registry with `error` explaining why the last interactive edit was
+    /// not applied, in the error column of `failed_plugins`. Errors not tied to
+    /// a listed plugin go to an extra row without a name, which is skipped when
+    /// the file is read back. Errors added one after the other share that row.
+    pub fn with_error(&self, error: &str, failed_plugins: &[String]) -> Self {
+        let mut registry = self.clone();
+        let mut error_shown = false;
+        for plugin_name in failed_plugins {
+            if let Some(plugin) = registry.plugins.get_mut(plugin_name) {
+                plugin.error = Some(error.to_string());
+                error_shown = true;
+            }
+        }
+
+        if !error_shown && let Some(error_row) = registry.plugins.get_mut("") {
+            let errors = [error_row.error.take(), Some(error.to_string())];
+            error_row.error = Some(errors.into_iter().flatten().collect::<Vec<_>>().join("; "));
+        } else if !error_shown {
+            registry.plugins.insert(
+                String::new(),
+                Plugin {
+                    error: Some(error.to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+        registry
+    }
+
     pub async fn to_file(&self, path: &Path) -> Result<&Self> {
         let mut wtr = WriterBuilder::new().has_headers(true).from_writer(vec![]);
         for plugin in self.plugins.values() {
@@ -60,7 +97,7 @@ impl Registry {
         let data = wtr.into_inner().map_err(|e| {
             VDPMError::RegistryOperationError(
                 "Failed to finalize CSV writer".into(),
-                RegistryError::from(e),
+                RegistryError::from(Box::new(e)),
             )
         })?;
 
@@ -70,30 +107,57 @@ impl Registry {
                 RegistryError::from(e),
             )
         })?;
+        // tokio finishes writes in the background, flushing waits for them so
+        // the file can be read back right away
         file.write_all(&data).await.map_err(|e| {
             VDPMError::RegistryOperationError(
                 "Failed to write CSV file".into(),
                 RegistryError::from(e),
             )
         })?;
+        file.flush().await.map_err(|e| {
+            VDPMError::RegistryOperationError(
+                "Failed to write CSV file".into(),
+                RegistryError::from(e),
+            )
+        })?;
 
         Ok(self)
     }
 
     pub async fn generate() -> Result<Self> {
-        let installed_plugins: HashSet<String> = Registry::get_installed_plugins()?;
+        let mut installed_plugins: HashSet<String> = Registry::get_installed_plugins()?;
+        // vdpm writes the companion plugin itself, it is not one of the user's
+        installed_plugins.remove(COMPANION_PLUGIN);
         let enabled_plugins: HashSet<String> = Registry::get_enabled_plugins().await?;
+        let install_records: InstallRecords =
+            InstallRecords::from_file(&get_install_records_file_path()?).await?;
+        let config: AppConfig = config_loader::load_or_create()?;
+        let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
 
         let plugins: BTreeMap<String, Plugin> = installed_plugins
             .into_iter()
             .map(|plugin| {
                 let is_enabled: bool = enabled_plugins.contains(plugin.as_str());
+                let install_record: Option<&InstallRecord> = install_records.plugins.get(&plugin);
+                let version: Option<String> = install_record
+                    .and_then(|record| record.version.clone())
+                    .or_else(|| Registry::read_module_version(&plugin_folder, &plugin));
+                let source: Option<String> =
+                    install_record.map(|record| locked_source(&plugin, record));
                 (
                     plugin.clone(),
                     Plugin {
                         name: plugin,
                         installed: true,
                         enabled: is_enabled,
+                        version,
+                        source,
+                        commit: install_record.and_then(|record| record.commit.clone()),
+                        pinned: install_record.is_some_and(|record| record.pinned),
+                        signed_by: install_record.and_then(|record| record.signed_by.clone()),
+                        installed_at: install_record.map(|record| record.installed_at.clone()),
+                        ..Default::default()
                     },
                 )
             })
@@ -102,34 +166,100 @@ impl Registry {
         Ok(Registry { plugins })
     }
 
-    fn get_installed_plugins() -> Result<HashSet<String>> {
+    /// The registry with the `description`, `size` and `sha256` of every
+    /// plugin filled in. They read all files of the plugins, so only the
+    /// interactive sheet and RPC clients, which show them, ask for them.
+    pub fn with_details(mut self) -> Result<Self> {
+        let config: AppConfig = config_loader::load_or_create()?;
+        let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+        for (plugin_name, plugin) in self.plugins.iter_mut() {
+            if !plugin.installed {
+                continue;
+            }
+            plugin.description = Registry::read_entry_source(&plugin_folder, plugin_name)
+                .as_deref()
+                .and_then(Registry::describe);
+            plugin.size = plugin_size(&plugin_folder, plugin_name).ok();
+            plugin.sha256 = sha256_plugin(&plugin_folder, plugin_name).ok().flatten();
+        }
+        Ok(self)
+    }
+
+    /// Falls back to the `__version__` declared by the plugin itself.
+    fn read_module_version(plugin_folder: &Path, plugin_name: &str) -> Option<String> {
+        explain::module_version(&Registry::read_entry_source(plugin_folder, plugin_name)?)
+    }
+
+    /// The source of the plugin module, which declares its `__version__` and
+    /// docstring.
+    fn read_entry_source(plugin_folder: &Path, plugin_name: &str) -> Option<String> {
+        let entry_file = plugin_entry_file(plugin_folder, plugin_name)?;
+        std::fs::read_to_string(entry_file).ok()
+    }
+
+    /// The first line of the module docstring.
+    fn describe(source: &str) -> Option<String> {
+        explain::explain(source)
+            .docstring?
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(String::from)
+    }
+
+    pub(crate) fn get_installed_plugins() -> Result<HashSet<String>> {
         // TODO @memedov, let's make it async also!
         let config: AppConfig = config_loader::load_or_create()?;
-        let installed_plugins: HashSet<String> = list_files_by_extension(
-            &get_home_dir().join(&config.settings.plugin_folder),
-            "py".to_string(),
-        );
+        let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+        let mut installed_plugins: HashSet<String> =
+            list_files_by_extension(&plugin_folder, "py".to_string());
+        installed_plugins.extend(list_package_dirs(&plugin_folder));
         Ok(installed_plugins)
     }
 
-    async fn get_enabled_plugins() -> Result<HashSet<String>> {
+    pub(crate) async fn get_enabled_plugins() -> Result<HashSet<String>> {
         let config: AppConfig = config_loader::load_or_create()?;
         let visidata_rc_content =
-            tokio::fs::read_to_string(&get_home_dir().join(&config.settings.rc_file))
-                .await
-                .map_err(|e| {
-                    VDPMError::VisidataRCError("VisidataRC could not be read!".into(), e)
-                })?;
-
-        let enabled_plugins: HashSet<String> = visidata_rc_content
-            .split("\n")
-            .filter(|line| line.starts_with("import plugins."))
-            .filter_map(|line| {
-                line.strip_prefix("import plugins.")
-                    .map(|enabled_plugin| enabled_plugin.to_string())
-            })
-            .collect();
+            visidatarc::read(&get_home_dir().join(&config.settings.rc_file)).await?;
+
+        Ok(visidatarc::parse_enabled_plugins(&visidata_rc_content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::{plugin, registry};
+
+    #[tokio::test]
+    async fn test_error_row_is_skipped_when_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry_file_path = dir.path().join("plugins.csv");
+        let registry = registry([plugin("hello", true)]);
+
+        registry
+            .with_error("Operation `uninstall hello` failed", &["hello".into()])
+            .to_file(&registry_file_path)
+            .await
+            .unwrap();
+        let read_back = Registry::from_file(&registry_file_path).await.unwrap();
+        assert_eq!(
+            read_back.plugins["hello"].error.as_deref(),
+            Some("Operation `uninstall hello` failed")
+        );
+
+        registry
+            .with_error("Failed to parse CSV registry file", &[])
+            .to_file(&registry_file_path)
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(&registry_file_path).unwrap();
+        assert!(content.contains(",Failed to parse CSV registry file\n"));
+        let read_back = Registry::from_file(&registry_file_path).await.unwrap();
+        assert_eq!(read_back.plugins.keys().collect::<Vec<_>>(), vec!["hello"]);
+        assert!(read_back.plugins["hello"].error.is_none());
 
-        Ok(enabled_plugins)
+        let registry = registry.with_error("first", &[]).with_error("second", &[]);
+        assert_eq!(registry.plugins[""].error.as_deref(), Some("first; second"));
     }
 }
@@ -0,0 +1,148 @@
+use crate::core::index::{IndexEntry, PluginIndex};
+
+/// An index entry matching a search, with the registry it was found in.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub registry: String,
+    pub entry: IndexEntry,
+    pub score: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub text: Option<String>,
+    pub tag: Option<String>,
+    pub author: Option<String>,
+}
+
+/// Whether all characters of `query` appear in `candidate` in order,
+/// e.g. `csvx` matches `csv_exporter`.
+fn is_subsequence(query: &str, candidate: &str) -> bool {
+    let mut candidate_chars = candidate.chars();
+    query
+        .chars()
+        .all(|query_char| candidate_chars.any(|candidate_char| candidate_char == query_char))
+}
+
+/// Scores how well `query` matches the entry, higher is better, `None` if it does not match.
+pub fn fuzzy_score(query: &str, entry: &IndexEntry) -> Option<u32> {
+    let query = query.to_lowercase();
+    let name = entry.name.to_lowercase();
+
+    if name == query {
+        Some(100)
+    } else if name.starts_with(&query) {
+        Some(80)
+    } else if name.contains(&query) {
+        Some(60)
+    } else if is_subsequence(&query, &name) {
+        Some(40)
+    } else if entry.description.to_lowercase().contains(&query) {
+        Some(20)
+    } else {
+        None
+    }
+}
+
+fn matches_filters(query: &SearchQuery, entry: &IndexEntry) -> bool {
+    let tag_matches = query.tag.as_ref().is_none_or(|tag| {
+        entry
+            .tags
+            .iter()
+            .any(|entry_tag| entry_tag.eq_ignore_ascii_case(tag))
+    });
+    let author_matches = query
+        .author
+        .as_ref()
+        .is_none_or(|author| entry.author.to_lowercase().contains(&author.to_lowercase()));
+    tag_matches && author_matches
+}
+
+/// Searches `indexes`, given in registry priority order. Hits are sorted by
+/// score, ties keep the registry order.
+pub fn search(indexes: &[(String, PluginIndex)], query: &SearchQuery) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = indexes
+        .iter()
+        .flat_map(|(registry, index)| {
+            index.plugins.iter().filter_map(move |entry| {
+                if !matches_filters(query, entry) {
+                    return None;
+                }
+                let score = match &query.text {
+                    Some(text) => fuzzy_score(text, entry)?,
+                    None => 0,
+                };
+                Some(SearchHit {
+                    registry: registry.clone(),
+                    entry: entry.clone(),
+                    score,
+                })
+            })
+        })
+        .collect();
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, description: &str, author: &str, tags: &[&str]) -> IndexEntry {
+        IndexEntry {
+            name: name.into(),
+            description: description.into(),
+            author: author.into(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            versions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_and_filters() {
+        let index = PluginIndex {
+            schema_version: 1,
+            plugins: vec![
+                entry("csv_exporter", "Exports sheets", "alice", &["io"]),
+                entry("csv", "Better csv", "bob", &["io", "formats"]),
+                entry(
+                    "charts",
+                    "Plots columns as csv friendly charts",
+                    "alice",
+                    &["viz"],
+                ),
+            ],
+        };
+        let indexes = vec![("official".to_string(), index)];
+
+        let names = |query: SearchQuery| -> Vec<String> {
+            search(&indexes, &query)
+                .into_iter()
+                .map(|hit| hit.entry.name)
+                .collect()
+        };
+
+        assert_eq!(
+            names(SearchQuery {
+                text: Some("csv".into()),
+                ..Default::default()
+            }),
+            vec!["csv", "csv_exporter", "charts"]
+        );
+        assert_eq!(
+            names(SearchQuery {
+                text: Some("cvxp".into()),
+                ..Default::default()
+            }),
+            vec!["csv_exporter"]
+        );
+        assert_eq!(
+            names(SearchQuery {
+                tag: Some("IO".into()),
+                author: Some("ali".into()),
+                ..Default::default()
+            }),
+            vec!["csv_exporter"]
+        );
+    }
+}
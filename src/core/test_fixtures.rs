@@ -0,0 +1,21 @@
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+
+/// An installed plugin without anything recorded about it.
+pub fn plugin(name: &str, enabled: bool) -> Plugin {
+    Plugin {
+        name: name.into(),
+        enabled,
+        installed: true,
+        ..Default::default()
+    }
+}
+
+pub fn registry(plugins: impl IntoIterator<Item = Plugin>) -> Registry {
+    Registry {
+        plugins: plugins
+            .into_iter()
+            .map(|plugin| (plugin.name.clone(), plugin))
+            .collect(),
+    }
+}
@@ -0,0 +1,196 @@
+use crate::error::{Result, VDPMError};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Compares plugin versions the way semver orders them, while still
+/// accepting the looser versions plugins are published with (`1.2`, `v0.3`,
+/// `2024.01.05`). Numeric parts compare numerically, a pre-release suffix
+/// (`1.0.0-beta`) orders before the release.
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    let (left_release, left_pre_release) = split_pre_release(left);
+    let (right_release, right_pre_release) = split_pre_release(right);
+
+    let left_parts: Vec<&str> = left_release.split('.').collect();
+    let right_parts: Vec<&str> = right_release.split('.').collect();
+    for i in 0..left_parts.len().max(right_parts.len()) {
+        let ordering = compare_parts(
+            left_parts.get(i).copied().unwrap_or("0"),
+            right_parts.get(i).copied().unwrap_or("0"),
+        );
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    match (left_pre_release, right_pre_release) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(left), Some(right)) => left
+            .split('.')
+            .zip(right.split('.'))
+            .map(|(left_part, right_part)| compare_parts(left_part, right_part))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| left.split('.').count().cmp(&right.split('.').count())),
+    }
+}
+
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    compare_versions(candidate, current) == Ordering::Greater
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Caret,
+    Tilde,
+}
+
+/// A version constraint like cargo's: comma separated comparators
+/// (`>=0.2, <1`), where a bare version means `^`. `*` matches any version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+    source: String,
+    comparators: Vec<(Op, String)>,
+}
+
+impl VersionReq {
+    pub fn parse(req: &str) -> Result<Self> {
+        let mut comparators = Vec::new();
+        for comparator in req.split(',').map(str::trim) {
+            if comparator == "*" {
+                continue;
+            }
+            let (op, version) = [
+                (">=", Op::GreaterEq),
+                ("<=", Op::LessEq),
+                (">", Op::Greater),
+                ("<", Op::Less),
+                ("=", Op::Exact),
+                ("^", Op::Caret),
+                ("~", Op::Tilde),
+            ]
+            .into_iter()
+            .find_map(|(prefix, op)| comparator.strip_prefix(prefix).map(|version| (op, version)))
+            .unwrap_or((Op::Caret, comparator));
+
+            let version = version.trim();
+            let (release, _) = split_pre_release(version);
+            if release.is_empty() || release.split('.').any(|part| part.parse::<u64>().is_err()) {
+                return Err(VDPMError::InvalidVersionConstraint(req.to_string()));
+            }
+            comparators.push((op, version.to_string()));
+        }
+        Ok(VersionReq {
+            source: req.trim().to_string(),
+            comparators,
+        })
+    }
+
+    pub fn matches(&self, version: &str) -> bool {
+        self.comparators.iter().all(|(op, req_version)| {
+            let ordering = compare_versions(version, req_version);
+            match op {
+                Op::Exact => ordering == Ordering::Equal,
+                Op::Greater => ordering == Ordering::Greater,
+                Op::GreaterEq => ordering != Ordering::Less,
+                Op::Less => ordering == Ordering::Less,
+                Op::LessEq => ordering != Ordering::Greater,
+                Op::Caret | Op::Tilde => {
+                    ordering != Ordering::Less
+                        && compare_versions(version, &upper_bound(*op, req_version))
+                            == Ordering::Less
+                }
+            }
+        })
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// The first version excluded by `^version` (the left-most non-zero part is
+/// bumped) or `~version` (the minor part is bumped, the major one if missing).
+fn upper_bound(op: Op, version: &str) -> String {
+    let (release, _) = split_pre_release(version);
+    let parts: Vec<u64> = release
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default())
+        .collect();
+    let bumped_index = match op {
+        Op::Tilde => parts.len().min(2) - 1,
+        _ => parts
+            .iter()
+            .position(|part| *part != 0)
+            .unwrap_or(parts.len() - 1),
+    };
+
+    let mut bound: Vec<String> = parts[..bumped_index].iter().map(u64::to_string).collect();
+    bound.push((parts[bumped_index] + 1).to_string());
+    bound.join(".")
+}
+
+fn split_pre_release(version: &str) -> (&str, Option<&str>) {
+    let version = version.trim();
+    let version = version
+        .strip_prefix(['v', 'V'])
+        .unwrap_or(version)
+        .split('+')
+        .next()
+        .unwrap_or_default();
+    match version.split_once('-') {
+        Some((release, pre_release)) => (release, Some(pre_release)),
+        None => (version, None),
+    }
+}
+
+fn compare_parts(left: &str, right: &str) -> Ordering {
+    match (left.parse::<u64>(), right.parse::<u64>()) {
+        (Ok(left), Ok(right)) => left.cmp(&right),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => left.cmp(right),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert!(is_newer("1.10.0", "1.9.3"));
+        assert!(is_newer("v2", "1.99"));
+        assert!(is_newer("1.0.0", "1.0.0-beta"));
+        assert!(is_newer("1.0.0-beta.10", "1.0.0-beta.2"));
+        assert!(!is_newer("1.0.0-beta", "1.0.0-beta.1"));
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.0+build5", "v1.2.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_version_req() {
+        let caret = VersionReq::parse("0.2.1").unwrap();
+        assert!(caret.matches("0.2.5"));
+        assert!(!caret.matches("0.3.0"));
+        assert!(!caret.matches("0.2.0"));
+
+        let tilde = VersionReq::parse("~1.4").unwrap();
+        assert!(tilde.matches("1.4.9"));
+        assert!(!tilde.matches("1.5.0"));
+
+        let range = VersionReq::parse(">=1.0, <2").unwrap();
+        assert!(range.matches("1.9.9"));
+        assert!(!range.matches("2.0.0"));
+        assert!(VersionReq::parse("*").unwrap().matches("0.0.1"));
+        assert!(VersionReq::parse("=v1.2").unwrap().matches("1.2.0"));
+        assert!(VersionReq::parse(">=latest").is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::interactive::registry_snapshot::RegistrySnapshot;
+use crate::interactive::InteractiveEvent;
 #[derive(Error, Debug)]
 pub enum VDPMError {
     #[error("File watcher error")]
@@ -9,17 +9,122 @@ pub enum VDPMError {
     #[error("Configuration error: {0}: {1}")]
     ConfigError(String, toml::de::Error),
 
+    #[error("Configuration error: {0}: {1}")]
+    ConfigEditError(String, toml_edit::TomlError),
+
+    #[error("Configuration error: {0}: {1}")]
+    ConfigWriteError(String, std::io::Error),
+
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
     #[error("Visidata RC error: {0}: {1}")]
     VisidataRCError(String, std::io::Error),
 
     #[error("Visidata RC error: {0}: {1}")]
     RegistryFileChangeHandlerError(
         String,
-        tokio::sync::mpsc::error::SendError<RegistrySnapshot>,
+        tokio::sync::mpsc::error::SendError<InteractiveEvent>,
     ),
 
     #[error("Reading registry failed: {0}: {1}")]
     RegistryOperationError(String, RegistryError),
+
+    #[error("Plugin({0}) is not installed")]
+    PluginNotInstalled(String),
+
+    #[error("Plugin({0}) is pinned, unpin it to update")]
+    PluginPinned(String),
+
+    #[error("Plugin({0}) is not enabled")]
+    PluginNotEnabled(String),
+
+    #[error("Plugin({0}) is already installed")]
+    PluginAlreadyInstalled(String),
+
+    #[error("Plugin file error: {0}: {1}")]
+    PluginFileError(String, std::io::Error),
+
+    #[error("Plugin({0}) could not be found in trash")]
+    PluginNotInTrash(String),
+
+    #[error("Unsupported install source: {0}")]
+    UnsupportedSource(String),
+
+    #[error("Invalid plugin({0}): {1}")]
+    InvalidPlugin(String, String),
+
+    #[error("Download failed: {0}: {1}")]
+    DownloadError(String, reqwest::Error),
+
+    #[error("Download failed: {0}: HTTP status {1}")]
+    DownloadStatusError(String, u16),
+
+    #[error("Checksum mismatch for {0}: expected sha256 {1}, got {2}")]
+    ChecksumMismatch(String, String, String),
+
+    #[error("Signature verification failed for {0}: {1}")]
+    SignatureError(String, String),
+
+    #[error("Git error: git {0}: {1}")]
+    GitError(String, String),
+
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+
+    #[error("Registry({0}) is not configured")]
+    UnknownRegistry(String),
+
+    #[error("Registry({0}) is already configured")]
+    RegistryAlreadyExists(String),
+
+    #[error("Plugin index error: {0}: {1}")]
+    IndexError(String, serde_json::Error),
+
+    #[error("Plugin index schema version {0} is not supported, expected at most {1}")]
+    UnsupportedIndexSchema(u32, u32),
+
+    #[error("Plugin index of registry({0}) is not cached, run `vdpm refresh` first")]
+    IndexNotCached(String),
+
+    #[error("Plugin({0}) could not be found in the plugin index")]
+    PluginNotFound(String),
+
+    #[error("Trash error: {0}: {1}")]
+    TrashError(String, serde_json::Error),
+
+    #[error("Invalid version constraint: {0}")]
+    InvalidVersionConstraint(String),
+
+    #[error("Manifest error: {0}: {1}")]
+    ManifestError(String, std::io::Error),
+
+    #[error("Manifest error: {0}: {1}")]
+    ManifestParseError(String, toml::de::Error),
+
+    #[error("Hashing failed: {0}: {1}")]
+    HashError(String, serde_json::Error),
+
+    #[error("Operation `{0}` failed, all operations of the batch were rolled back: {1}")]
+    OperationFailed(String, Box<VDPMError>),
+
+    #[error("Operation `{0}` failed ({1}) and rolling back the batch failed too: {2}")]
+    RollbackFailed(String, Box<VDPMError>, std::io::Error),
+
+    #[error("Lock file error: {0}: {1}")]
+    LockFileError(String, std::io::Error),
+
+    #[error("Lock file error: {0}: {1}")]
+    LockFileParseError(String, toml::de::Error),
+
+    #[error("Lock file error: {0}: {1}")]
+    LockFileSerializeError(String, toml::ser::Error),
+
+    #[error("Lock file version {0} is not supported, expected at most {1}")]
+    UnsupportedLockFileVersion(u32, u32),
+
+    #[error("JSON-RPC error: {0}: {1}")]
+    RpcError(String, std::io::Error),
 }
 
 #[derive(Error, Debug)]
@@ -28,7 +133,7 @@ pub enum RegistryError {
     Csv(#[from] csv::Error),
 
     #[error("CSV Error: {0}")]
-    CSVWriter(#[from] csv::IntoInnerError<csv::Writer<Vec<u8>>>),
+    CSVWriter(#[from] Box<csv::IntoInnerError<csv::Writer<Vec<u8>>>>),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -0,0 +1,201 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub fn sha256_bytes(content: &[u8]) -> String {
+    to_hex(&Sha256::digest(content))
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    Ok(sha256_bytes(&fs::read(path)?))
+}
+
+fn collect_files(folder: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path.file_name().is_some_and(|name| name != "__pycache__") {
+                collect_files(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Files making up an installed plugin, relative to `plugin_folder` and sorted:
+/// `<name>.py` and everything in the `<name>/` package besides `__pycache__`.
+pub fn plugin_files(plugin_folder: &Path, plugin_name: &str) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let module = plugin_folder.join(format!("{}.py", plugin_name));
+    if module.is_file() {
+        files.push(module);
+    }
+    let package = plugin_folder.join(plugin_name);
+    if package.is_dir() {
+        collect_files(&package, &mut files)?;
+    }
+
+    let mut relative_files: Vec<PathBuf> = files
+        .into_iter()
+        .filter_map(|file| file.strip_prefix(plugin_folder).ok().map(Path::to_path_buf))
+        .collect();
+    relative_files.sort();
+    Ok(relative_files)
+}
+
+/// Total bytes of the files making up an installed plugin.
+pub fn plugin_size(plugin_folder: &Path, plugin_name: &str) -> io::Result<u64> {
+    plugin_files(plugin_folder, plugin_name)?
+        .into_iter()
+        .map(|file| Ok(fs::metadata(plugin_folder.join(file))?.len()))
+        .sum()
+}
+
+/// A single digest over all files of a plugin, covering both their paths and
+/// their content, so renames are detected as well as edits.
+pub fn sha256_plugin(plugin_folder: &Path, plugin_name: &str) -> io::Result<Option<String>> {
+    let files = plugin_files(plugin_folder, plugin_name)?;
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    let mut hasher = Sha256::new();
+    for file in files {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(sha256_file(&plugin_folder.join(&file))?.as_bytes());
+        hasher.update([0]);
+    }
+    Ok(Some(to_hex(&hasher.finalize())))
+}
+
+/// The sha256 of every file of a plugin, keyed by its path relative to
+/// `plugin_folder`.
+pub fn sha256_plugin_files(
+    plugin_folder: &Path,
+    plugin_name: &str,
+) -> io::Result<BTreeMap<String, String>> {
+    plugin_files(plugin_folder, plugin_name)?
+        .into_iter()
+        .map(|file| {
+            let sha256 = sha256_file(&plugin_folder.join(&file))?;
+            Ok((file.to_string_lossy().to_string(), sha256))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileStatus {
+    Modified,
+    Missing,
+    /// Present in the plugin but not part of what was installed
+    Unknown,
+}
+
+impl fmt::Display for FileStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileStatus::Modified => write!(f, "modified"),
+            FileStatus::Missing => write!(f, "missing"),
+            FileStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Compares the file hashes recorded at install time with the current ones and
+/// returns every file that differs, sorted by path.
+pub fn compare_plugin_files(
+    recorded: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<(String, FileStatus)> {
+    let mut changes: Vec<(String, FileStatus)> = recorded
+        .iter()
+        .filter_map(|(file, recorded_sha256)| match current.get(file) {
+            None => Some((file.clone(), FileStatus::Missing)),
+            Some(sha256) if sha256 != recorded_sha256 => Some((file.clone(), FileStatus::Modified)),
+            Some(_) => None,
+        })
+        .chain(
+            current
+                .keys()
+                .filter(|file| !recorded.contains_key(*file))
+                .map(|file| (file.clone(), FileStatus::Unknown)),
+        )
+        .collect();
+    changes.sort_by(|left, right| left.0.cmp(&right.0));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256_bytes(b"vdpm"),
+            "aff8a3009ccbcc964adffe49388567b99b852620d47e3f581f55d1ccd11b9506"
+        );
+    }
+
+    #[test]
+    fn test_sha256_plugin_detects_changes() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        assert!(
+            sha256_plugin(plugin_folder.path(), "hello")
+                .unwrap()
+                .is_none()
+        );
+
+        fs::create_dir_all(plugin_folder.path().join("hello/__pycache__")).unwrap();
+        fs::write(plugin_folder.path().join("hello/__init__.py"), "x = 1").unwrap();
+        let before = sha256_plugin(plugin_folder.path(), "hello").unwrap();
+        fs::write(plugin_folder.path().join("hello/__pycache__/x.pyc"), "").unwrap();
+        assert_eq!(
+            sha256_plugin(plugin_folder.path(), "hello").unwrap(),
+            before
+        );
+
+        fs::write(plugin_folder.path().join("hello/__init__.py"), "x = 2").unwrap();
+        assert_ne!(
+            sha256_plugin(plugin_folder.path(), "hello").unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn test_compare_plugin_files() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        fs::create_dir(plugin_folder.path().join("hello")).unwrap();
+        fs::write(plugin_folder.path().join("hello/__init__.py"), "x = 1").unwrap();
+        fs::write(plugin_folder.path().join("hello/sheet.py"), "y = 1").unwrap();
+        let recorded = sha256_plugin_files(plugin_folder.path(), "hello").unwrap();
+        assert_eq!(
+            recorded.keys().collect::<Vec<_>>(),
+            vec!["hello/__init__.py", "hello/sheet.py"]
+        );
+
+        fs::write(plugin_folder.path().join("hello/__init__.py"), "x = 2").unwrap();
+        fs::remove_file(plugin_folder.path().join("hello/sheet.py")).unwrap();
+        fs::write(plugin_folder.path().join("hello/extra.py"), "").unwrap();
+        let current = sha256_plugin_files(plugin_folder.path(), "hello").unwrap();
+        assert_eq!(
+            compare_plugin_files(&recorded, &current),
+            vec![
+                ("hello/__init__.py".to_string(), FileStatus::Modified),
+                ("hello/extra.py".to_string(), FileStatus::Unknown),
+                ("hello/sheet.py".to_string(), FileStatus::Missing),
+            ]
+        );
+        assert!(compare_plugin_files(&current, &current).is_empty());
+    }
+}
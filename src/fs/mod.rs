@@ -1,2 +1,6 @@
+pub mod checksum;
 pub mod operations;
 pub mod paths;
+pub mod transaction;
+pub mod trash;
+pub mod visidatarc;
@@ -4,7 +4,7 @@ use crate::error::{Result, VDPMError};
 use std::collections::HashSet;
 use std::fs;
 use std::io::ErrorKind;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub fn list_files_by_extension(dir: &Path, extension: String) -> HashSet<String> {
     let mut files = HashSet::new();
@@ -14,18 +14,46 @@ pub fn list_files_by_extension(dir: &Path, extension: String) -> HashSet<String>
             .filter_map(std::result::Result::ok)
         {
             let path = entry.path();
-            if path.extension() == Some(extension.as_ref()) {
-                if let Some(file_name) = path.file_stem() {
-                    if let Some(file_str) = file_name.to_str() {
-                        files.insert(file_str.to_string());
-                    }
-                }
+            if path.extension() == Some(extension.as_ref())
+                && let Some(file_name) = path.file_stem()
+                && let Some(file_str) = file_name.to_str()
+            {
+                files.insert(file_str.to_string());
             }
         }
     }
     files
 }
 
+/// Lists the directories of `dir` which are importable python packages.
+pub fn list_package_dirs(dir: &Path) -> HashSet<String> {
+    let mut packages = HashSet::new();
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)
+            .unwrap()
+            .filter_map(std::result::Result::ok)
+        {
+            let path = entry.path();
+            if path.join("__init__.py").is_file()
+                && let Some(dir_name) = path.file_name()
+                && let Some(dir_str) = dir_name.to_str()
+            {
+                packages.insert(dir_str.to_string());
+            }
+        }
+    }
+    packages
+}
+
+/// The file VisiData imports for the plugin: `<name>.py` or `<name>/__init__.py`.
+pub fn plugin_entry_file(plugin_folder: &Path, plugin_name: &str) -> Option<PathBuf> {
+    let module = plugin_folder.join(format!("{}.py", plugin_name));
+    let package_init = plugin_folder.join(plugin_name).join("__init__.py");
+    [module, package_init]
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
 pub async fn create_visidata_rc(rc_file_path: &Path) -> Result<()> {
     match OpenOptions::new()
         .write(true)
@@ -41,3 +69,31 @@ pub async fn create_visidata_rc(rc_file_path: &Path) -> Result<()> {
         )),
     }
 }
+
+pub fn copy_recursively(source: &Path, destination: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        fs::create_dir_all(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursively(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(source, destination)?;
+    }
+    Ok(())
+}
+
+/// Moves a file or directory, falling back to copy and delete when
+/// `source` and `destination` are on different file systems.
+pub fn move_path(source: &Path, destination: &Path) -> std::io::Result<()> {
+    if fs::rename(source, destination).is_ok() {
+        return Ok(());
+    }
+
+    copy_recursively(source, destination)?;
+    if source.is_dir() {
+        fs::remove_dir_all(source)
+    } else {
+        fs::remove_file(source)
+    }
+}
@@ -22,3 +22,51 @@ pub fn get_registry_file_path() -> Result<PathBuf> {
     let vdpm_config_file_path = vdpm_config_directory.join(config.settings.plugin_manager_file);
     Ok(vdpm_config_file_path)
 }
+
+pub fn get_trash_folder_path() -> Result<PathBuf> {
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let vdpm_config_directory =
+        create_vdpm_config_directory(&config.settings.vdpm_config_folder_path);
+    Ok(vdpm_config_directory.join("trash"))
+}
+
+pub fn get_install_records_file_path() -> Result<PathBuf> {
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let vdpm_config_directory =
+        create_vdpm_config_directory(&config.settings.vdpm_config_folder_path);
+    Ok(vdpm_config_directory.join("installed.json"))
+}
+
+pub fn get_cache_folder_path() -> Result<PathBuf> {
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let vdpm_config_directory =
+        create_vdpm_config_directory(&config.settings.vdpm_config_folder_path);
+    Ok(vdpm_config_directory.join("cache"))
+}
+
+pub fn get_backups_folder_path() -> Result<PathBuf> {
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let vdpm_config_directory =
+        create_vdpm_config_directory(&config.settings.vdpm_config_folder_path);
+    Ok(vdpm_config_directory.join("backups"))
+}
+
+pub fn get_lock_file_path() -> Result<PathBuf> {
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let vdpm_config_directory =
+        create_vdpm_config_directory(&config.settings.vdpm_config_folder_path);
+    Ok(vdpm_config_directory.join("vdpm.lock"))
+}
+
+pub fn get_socket_path() -> Result<PathBuf> {
+    let config: AppConfig = config_loader::load_or_create()?;
+
+    let vdpm_config_directory =
+        create_vdpm_config_directory(&config.settings.vdpm_config_folder_path);
+    Ok(vdpm_config_directory.join("vdpm.sock"))
+}
@@ -0,0 +1,180 @@
+use crate::fs::operations::copy_recursively;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A snapshot of everything a batch of plugin operations may change: state
+/// files like .visidatarc and the install records, the entries of folders
+/// like the trash and the backups, and the files of the plugins involved.
+/// Rolling back puts all of them back as they were, so a failing batch never
+/// leaves half of its operations applied.
+pub struct Transaction {
+    backup_folder: TempDir,
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+    entry_folders: Vec<(PathBuf, HashSet<OsString>)>,
+    plugin_folder: PathBuf,
+    plugin_names: Vec<String>,
+}
+
+fn plugin_paths(plugin_folder: &Path, plugin_name: &str) -> [PathBuf; 2] {
+    [
+        plugin_folder.join(format!("{}.py", plugin_name)),
+        plugin_folder.join(plugin_name),
+    ]
+}
+
+fn entry_names(folder: &Path) -> io::Result<HashSet<OsString>> {
+    match fs::read_dir(folder) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect(),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(e),
+    }
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+impl Transaction {
+    /// Snapshots `files` and the plugins `plugin_names`, and remembers the
+    /// entries of `entry_folders` so entries added later are removed on
+    /// rollback.
+    pub fn begin(
+        files: &[PathBuf],
+        entry_folders: &[PathBuf],
+        plugin_folder: &Path,
+        plugin_names: &[String],
+    ) -> io::Result<Self> {
+        let backup_folder = tempfile::tempdir()?;
+
+        let files = files
+            .iter()
+            .map(|file| match fs::read(file) {
+                Ok(content) => Ok((file.clone(), Some(content))),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok((file.clone(), None)),
+                Err(e) => Err(e),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let entry_folders = entry_folders
+            .iter()
+            .map(|folder| Ok((folder.clone(), entry_names(folder)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        for plugin_name in plugin_names {
+            for path in plugin_paths(plugin_folder, plugin_name) {
+                if let Some(file_name) = path.file_name()
+                    && path.exists()
+                {
+                    copy_recursively(&path, &backup_folder.path().join(file_name))?;
+                }
+            }
+        }
+
+        Ok(Transaction {
+            backup_folder,
+            files,
+            entry_folders,
+            plugin_folder: plugin_folder.to_path_buf(),
+            plugin_names: plugin_names.to_vec(),
+        })
+    }
+
+    /// Restores every snapshotted file and plugin. Files and folder entries
+    /// that did not exist when the transaction began are removed.
+    pub fn rollback(self) -> io::Result<()> {
+        for (file, content) in &self.files {
+            match content {
+                Some(content) => fs::write(file, content)?,
+                None if file.exists() => fs::remove_file(file)?,
+                None => {}
+            }
+        }
+
+        for (folder, names) in &self.entry_folders {
+            for name in entry_names(folder)?.difference(names) {
+                remove_path(&folder.join(name))?;
+            }
+        }
+
+        for plugin_name in &self.plugin_names {
+            for path in plugin_paths(&self.plugin_folder, plugin_name) {
+                if path.exists() {
+                    remove_path(&path)?;
+                }
+                if let Some(file_name) = path.file_name() {
+                    let backup_path = self.backup_folder.path().join(file_name);
+                    if backup_path.exists() {
+                        copy_recursively(&backup_path, &path)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollback_restores_files_and_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let rc_file = dir.path().join(".visidatarc");
+        let records_file = dir.path().join("installed.json");
+        let trash_folder = dir.path().join("trash");
+        let plugin_folder = dir.path().join("plugins");
+        fs::create_dir_all(plugin_folder.join("pkg")).unwrap();
+        fs::write(&rc_file, "import plugins.hello\n").unwrap();
+        fs::write(plugin_folder.join("hello.py"), "x = 1").unwrap();
+        fs::write(plugin_folder.join("pkg/__init__.py"), "y = 1").unwrap();
+        fs::create_dir_all(trash_folder.join("20260101000000000")).unwrap();
+
+        let transaction = Transaction::begin(
+            &[rc_file.clone(), records_file.clone()],
+            &[trash_folder.clone(), plugin_folder.clone()],
+            &plugin_folder,
+            &["hello".into(), "pkg".into(), "new".into()],
+        )
+        .unwrap();
+
+        fs::write(&rc_file, "").unwrap();
+        fs::write(&records_file, "{}").unwrap();
+        fs::remove_file(plugin_folder.join("hello.py")).unwrap();
+        fs::write(plugin_folder.join("pkg/__init__.py"), "y = 2").unwrap();
+        fs::write(plugin_folder.join("pkg/extra.py"), "").unwrap();
+        fs::write(plugin_folder.join("new.py"), "").unwrap();
+        fs::write(plugin_folder.join("renamed.py"), "").unwrap();
+        fs::create_dir_all(trash_folder.join("20260102000000000")).unwrap();
+
+        transaction.rollback().unwrap();
+        assert_eq!(
+            fs::read_to_string(&rc_file).unwrap(),
+            "import plugins.hello\n"
+        );
+        assert!(!records_file.exists());
+        assert_eq!(
+            fs::read_to_string(plugin_folder.join("hello.py")).unwrap(),
+            "x = 1"
+        );
+        assert_eq!(
+            fs::read_to_string(plugin_folder.join("pkg/__init__.py")).unwrap(),
+            "y = 1"
+        );
+        assert!(!plugin_folder.join("pkg/extra.py").exists());
+        assert!(!plugin_folder.join("new.py").exists());
+        assert!(!plugin_folder.join("renamed.py").exists());
+        assert!(trash_folder.join("20260101000000000").exists());
+        assert!(!trash_folder.join("20260102000000000").exists());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::core::install_record::InstallRecord;
+use crate::error::{Result, VDPMError};
+use crate::fs::operations::move_path;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TRASH_ENTRY_EXTENSION: &str = "trash.json";
+
+/// Metadata stored next to the trashed plugin files, so that `vdpm restore`
+/// can put them back in the state they were uninstalled from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub plugin_name: String,
+    pub enabled: bool,
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub install_record: Option<InstallRecord>,
+}
+
+fn trash_entry_file_name(plugin_name: &str) -> String {
+    format!("{}.{}", plugin_name, TRASH_ENTRY_EXTENSION)
+}
+
+fn plugin_file_names(plugin_name: &str) -> [String; 2] {
+    [format!("{}.py", plugin_name), plugin_name.to_string()]
+}
+
+fn io_error(message: String) -> impl FnOnce(std::io::Error) -> VDPMError {
+    move |e| VDPMError::PluginFileError(message, e)
+}
+
+/// Moves `<plugin_name>.py` and its companion package directory (if any) from
+/// `plugin_folder` into `trash_folder/<timestamp>/` and returns that folder.
+pub fn move_to_trash(
+    trash_folder: &Path,
+    plugin_folder: &Path,
+    plugin_name: &str,
+    enabled: bool,
+    install_record: Option<InstallRecord>,
+) -> Result<PathBuf> {
+    let timestamp = Local::now().format("%Y%m%d%H%M%S%3f").to_string();
+    let trash_entry_folder = trash_folder.join(timestamp);
+    fs::create_dir_all(&trash_entry_folder)
+        .map_err(io_error("Failed to create trash folder".into()))?;
+
+    let mut files = Vec::new();
+    for file_name in plugin_file_names(plugin_name) {
+        let source = plugin_folder.join(&file_name);
+        if source.exists() {
+            move_path(&source, &trash_entry_folder.join(&file_name)).map_err(io_error(format!(
+                "Failed to move {} to trash",
+                source.display()
+            )))?;
+            files.push(file_name);
+        }
+    }
+
+    let trash_entry = TrashEntry {
+        plugin_name: plugin_name.to_string(),
+        enabled,
+        files,
+        install_record,
+    };
+    let trash_entry_json = serde_json::to_string_pretty(&trash_entry)
+        .map_err(|e| VDPMError::TrashError("Failed to serialize trash entry".into(), e))?;
+    fs::write(
+        trash_entry_folder.join(trash_entry_file_name(plugin_name)),
+        trash_entry_json,
+    )
+    .map_err(io_error("Failed to write trash entry".into()))?;
+
+    Ok(trash_entry_folder)
+}
+
+/// Finds the most recently trashed copy of the plugin.
+pub fn find_latest_trash_entry_folder(trash_folder: &Path, plugin_name: &str) -> Option<PathBuf> {
+    let mut trash_entry_folders: Vec<PathBuf> = fs::read_dir(trash_folder)
+        .ok()?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.join(trash_entry_file_name(plugin_name)).is_file())
+        .collect();
+    trash_entry_folders.sort();
+    trash_entry_folders.pop()
+}
+
+/// Moves the most recently trashed copy of the plugin back into `plugin_folder`.
+pub fn restore_from_trash(
+    trash_folder: &Path,
+    plugin_folder: &Path,
+    plugin_name: &str,
+) -> Result<TrashEntry> {
+    let trash_entry_folder = find_latest_trash_entry_folder(trash_folder, plugin_name)
+        .ok_or_else(|| VDPMError::PluginNotInTrash(plugin_name.to_string()))?;
+    restore_trash_entry(&trash_entry_folder, plugin_folder, plugin_name)
+}
+
+/// Moves the plugin trashed in `trash_entry_folder` back into `plugin_folder`.
+pub fn restore_trash_entry(
+    trash_entry_folder: &Path,
+    plugin_folder: &Path,
+    plugin_name: &str,
+) -> Result<TrashEntry> {
+    let trash_entry_path = trash_entry_folder.join(trash_entry_file_name(plugin_name));
+
+    let trash_entry_json = fs::read_to_string(&trash_entry_path)
+        .map_err(io_error("Failed to read trash entry".into()))?;
+    let trash_entry: TrashEntry = serde_json::from_str(&trash_entry_json)
+        .map_err(|e| VDPMError::TrashError("Failed to parse trash entry".into(), e))?;
+
+    if let Some(existing) = trash_entry
+        .files
+        .iter()
+        .find(|file_name| plugin_folder.join(file_name).exists())
+    {
+        return Err(VDPMError::PluginAlreadyInstalled(existing.clone()));
+    }
+
+    fs::create_dir_all(plugin_folder).map_err(io_error("Failed to create plugin folder".into()))?;
+    for file_name in &trash_entry.files {
+        move_path(
+            &trash_entry_folder.join(file_name),
+            &plugin_folder.join(file_name),
+        )
+        .map_err(io_error(format!("Failed to restore {}", file_name)))?;
+    }
+
+    fs::remove_file(&trash_entry_path).map_err(io_error("Failed to remove trash entry".into()))?;
+    // Other plugins may share the same timestamp folder, so only remove it once it is empty.
+    let _ = fs::remove_dir(trash_entry_folder);
+
+    Ok(trash_entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uninstall_and_restore_round_trip() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let trash_folder = tempfile::tempdir().unwrap();
+        fs::write(plugin_folder.path().join("hello.py"), "print('hello')").unwrap();
+        fs::create_dir(plugin_folder.path().join("hello")).unwrap();
+        fs::write(plugin_folder.path().join("hello").join("__init__.py"), "").unwrap();
+        fs::write(plugin_folder.path().join("other.py"), "").unwrap();
+
+        let trash_entry_folder = move_to_trash(
+            trash_folder.path(),
+            plugin_folder.path(),
+            "hello",
+            true,
+            None,
+        )
+        .unwrap();
+        assert!(trash_entry_folder.join("hello.py").is_file());
+        assert!(
+            trash_entry_folder
+                .join("hello")
+                .join("__init__.py")
+                .is_file()
+        );
+        assert!(!plugin_folder.path().join("hello.py").exists());
+        assert!(plugin_folder.path().join("other.py").exists());
+
+        let trash_entry =
+            restore_from_trash(trash_folder.path(), plugin_folder.path(), "hello").unwrap();
+        assert!(trash_entry.enabled);
+        assert_eq!(
+            fs::read_to_string(plugin_folder.path().join("hello.py")).unwrap(),
+            "print('hello')"
+        );
+        assert!(
+            plugin_folder
+                .path()
+                .join("hello")
+                .join("__init__.py")
+                .is_file()
+        );
+        assert!(!trash_entry_folder.exists());
+    }
+
+    #[test]
+    fn test_restore_unknown_plugin_fails() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let trash_folder = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            restore_from_trash(trash_folder.path(), plugin_folder.path(), "hello"),
+            Err(VDPMError::PluginNotInTrash(_))
+        ));
+    }
+}
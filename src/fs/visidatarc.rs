@@ -0,0 +1,196 @@
+use crate::error::{Result, VDPMError};
+use std::collections::HashSet;
+use std::path::Path;
+
+pub const IMPORT_PREFIX: &str = "import plugins.";
+pub const DISABLED_MARKER: &str = "# vdpm-disabled: ";
+
+pub fn import_line(plugin_name: &str) -> String {
+    format!("{}{}", IMPORT_PREFIX, plugin_name)
+}
+
+/// The plugin a top-level import line imports, without a trailing comment.
+/// Indented imports, like ones inside an `if`, only run conditionally and do
+/// not count.
+fn imported_plugin(line: &str) -> Option<&str> {
+    let imported_plugin = line
+        .strip_prefix(IMPORT_PREFIX)?
+        .split('#')
+        .next()
+        .unwrap_or_default()
+        .trim();
+    (!imported_plugin.is_empty()).then_some(imported_plugin)
+}
+
+fn imports_plugin(line: &str, plugin_name: &str) -> bool {
+    imported_plugin(line) == Some(plugin_name)
+}
+
+fn is_disabled_import(line: &str, plugin_name: &str) -> bool {
+    line.trim_start()
+        .strip_prefix(DISABLED_MARKER)
+        .is_some_and(|import| imports_plugin(import, plugin_name))
+}
+
+pub fn parse_enabled_plugins(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .filter_map(imported_plugin)
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the new rc content with the plugin import appended (or a previously
+/// commented out import restored), or `None` when the plugin is already imported.
+pub fn add_import(content: &str, plugin_name: &str) -> Option<String> {
+    if parse_enabled_plugins(content).contains(plugin_name) {
+        return None;
+    }
+
+    if content
+        .lines()
+        .any(|line| is_disabled_import(line, plugin_name))
+    {
+        let mut restored = false;
+        let new_content = content
+            .split_inclusive('\n')
+            .map(|line| {
+                if !restored && is_disabled_import(line, plugin_name) {
+                    restored = true;
+                    let line = line.trim_start();
+                    line.strip_prefix(DISABLED_MARKER)
+                        .unwrap_or(line)
+                        .to_string()
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        return Some(new_content);
+    }
+
+    let mut new_content = content.to_string();
+    if !new_content.is_empty() && !new_content.ends_with('\n') {
+        new_content.push('\n');
+    }
+    new_content.push_str(&import_line(plugin_name));
+    new_content.push('\n');
+    Some(new_content)
+}
+
+/// Returns the new rc content without the plugin import, or `None` when the
+/// plugin is not imported. With `comment_out` the import line is kept behind
+/// the [`DISABLED_MARKER`]. Every other line is left untouched.
+pub fn remove_import(content: &str, plugin_name: &str, comment_out: bool) -> Option<String> {
+    if !content
+        .lines()
+        .any(|line| imports_plugin(line, plugin_name))
+    {
+        return None;
+    }
+
+    let new_content = content
+        .split_inclusive('\n')
+        .filter_map(|line| {
+            if !imports_plugin(line, plugin_name) {
+                Some(line.to_string())
+            } else if comment_out {
+                Some(format!("{}{}", DISABLED_MARKER, line.trim_start()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    Some(new_content)
+}
+
+pub async fn read(rc_file_path: &Path) -> Result<String> {
+    tokio::fs::read_to_string(rc_file_path)
+        .await
+        .map_err(|e| VDPMError::VisidataRCError("VisidataRC could not be read!".into(), e))
+}
+
+pub async fn write(rc_file_path: &Path, content: &str) -> Result<()> {
+    tokio::fs::write(rc_file_path, content)
+        .await
+        .map_err(|e| VDPMError::VisidataRCError("VisidataRC could not be written!".into(), e))
+}
+
+/// Adds the plugin import to the rc file, returns `false` if it was already there.
+pub async fn enable_plugin(rc_file_path: &Path, plugin_name: &str) -> Result<bool> {
+    let content = read(rc_file_path).await?;
+    match add_import(&content, plugin_name) {
+        Some(new_content) => {
+            write(rc_file_path, &new_content).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Removes the plugin import from the rc file, returns `false` if it was not there.
+pub async fn disable_plugin(
+    rc_file_path: &Path,
+    plugin_name: &str,
+    comment_out: bool,
+) -> Result<bool> {
+    let content = read(rc_file_path).await?;
+    match remove_import(&content, plugin_name, comment_out) {
+        Some(new_content) => {
+            write(rc_file_path, &new_content).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_import_keeps_existing_content() {
+        let content = "# my rc\noptions.min_memory_mb = 100";
+        let new_content = add_import(content, "hello").unwrap();
+        assert_eq!(
+            new_content,
+            "# my rc\noptions.min_memory_mb = 100\nimport plugins.hello\n"
+        );
+    }
+
+    #[test]
+    fn test_add_import_is_idempotent() {
+        let content = "import plugins.hello\n";
+        assert!(add_import(content, "hello").is_none());
+        assert!(add_import(content, "hello_world").is_some());
+    }
+
+    #[test]
+    fn test_parse_enabled_plugins_only_counts_top_level_imports() {
+        let content = "import plugins.hello  # greets\r\nif False:\n    import plugins.maybe\n";
+        assert_eq!(
+            parse_enabled_plugins(content),
+            HashSet::from(["hello".to_string()])
+        );
+        assert!(add_import(content, "hello").is_none());
+    }
+
+    #[test]
+    fn test_remove_import_preserves_other_lines() {
+        let content = "# my rc\n\nimport plugins.hello\r\nimport plugins.hello_world\n";
+        assert_eq!(
+            remove_import(content, "hello", false).unwrap(),
+            "# my rc\n\nimport plugins.hello_world\n"
+        );
+        assert!(remove_import(content, "missing", false).is_none());
+    }
+
+    #[test]
+    fn test_commented_out_import_is_restored_on_enable() {
+        let content = "# my rc\nimport plugins.hello\n";
+        let disabled = remove_import(content, "hello", true).unwrap();
+        assert_eq!(disabled, "# my rc\n# vdpm-disabled: import plugins.hello\n");
+        assert!(parse_enabled_plugins(&disabled).is_empty());
+        assert_eq!(add_import(&disabled, "hello").unwrap(), content);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::error::{Result, VDPMError};
+use crate::sources::is_valid_plugin_name;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+/// The VisiData plugin behind `vdpm interactive --native`.
+pub const COMPANION_PLUGIN: &str = "vdpm_sheet";
+const COMPANION_PLUGIN_SOURCE: &str = include_str!("vdpm_sheet.py");
+
+/// Writes the companion plugin into `plugin_folder`, replacing an older copy
+/// so it always speaks the protocol of this vdpm.
+pub async fn install(plugin_folder: &Path) -> Result<PathBuf> {
+    let path = plugin_folder.join(format!("{}.py", COMPANION_PLUGIN));
+    if tokio::fs::read_to_string(&path).await.ok().as_deref() == Some(COMPANION_PLUGIN_SOURCE) {
+        return Ok(path);
+    }
+    let install_error =
+        |e| VDPMError::PluginFileError(format!("Failed to install {}", path.display()), e);
+    tokio::fs::create_dir_all(plugin_folder)
+        .await
+        .map_err(install_error)?;
+    tokio::fs::write(&path, COMPANION_PLUGIN_SOURCE)
+        .await
+        .map_err(install_error)?;
+    Ok(path)
+}
+
+/// The module VisiData imports the companion plugin in `plugin_folder` as.
+/// VisiData imports modules relative to `visidata_dir`, so a plugin folder in
+/// it gives a package path like `plugins.vdpm_sheet`. Any other plugin folder
+/// is returned as well, it has to be put on `PYTHONPATH`.
+pub fn import_path(plugin_folder: &Path, visidata_dir: &Path) -> (String, Option<PathBuf>) {
+    let packages: Option<Vec<&str>> =
+        plugin_folder
+            .strip_prefix(visidata_dir)
+            .ok()
+            .and_then(|relative_folder| {
+                relative_folder
+                    .components()
+                    .map(|component| match component {
+                        Component::Normal(package) => package
+                            .to_str()
+                            .filter(|package| is_valid_plugin_name(package)),
+                        _ => None,
+                    })
+                    .collect()
+            });
+    match packages {
+        Some(mut packages) => {
+            packages.push(COMPANION_PLUGIN);
+            (packages.join("."), None)
+        }
+        None => (
+            COMPANION_PLUGIN.to_string(),
+            Some(plugin_folder.to_path_buf()),
+        ),
+    }
+}
+
+/// `PYTHONPATH` for VisiData with `plugin_folder` in front of `python_path`,
+/// so the companion plugin imports as a top-level module.
+pub fn python_path(plugin_folder: &Path, python_path: Option<OsString>) -> Result<OsString> {
+    let paths = std::iter::once(plugin_folder.to_path_buf()).chain(
+        python_path
+            .iter()
+            .flat_map(std::env::split_paths)
+            .collect::<Vec<_>>(),
+    );
+    std::env::join_paths(paths).map_err(|e| {
+        VDPMError::InvalidArguments(format!(
+            "{} can not be added to PYTHONPATH: {}",
+            plugin_folder.display(),
+            e
+        ))
+    })
+}
+
+/// Quotes `value` for the `shlex.split` of the companion plugin.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_install_replaces_older_copy() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let path = plugin_folder.path().join("vdpm_sheet.py");
+        std::fs::write(&path, "# older copy").unwrap();
+
+        assert_eq!(install(plugin_folder.path()).await.unwrap(), path);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            COMPANION_PLUGIN_SOURCE
+        );
+        assert_eq!(shell_quote("/opt/it's/vdpm"), r"'/opt/it'\''s/vdpm'");
+        assert_eq!(
+            import_path(
+                Path::new("/home/u/.visidata/plugins"),
+                Path::new("/home/u/.visidata")
+            ),
+            ("plugins.vdpm_sheet".to_string(), None)
+        );
+        assert_eq!(
+            import_path(
+                Path::new("/home/u/vd-plugins"),
+                Path::new("/home/u/.visidata")
+            ),
+            (
+                "vdpm_sheet".to_string(),
+                Some(PathBuf::from("/home/u/vd-plugins"))
+            )
+        );
+        assert_eq!(
+            python_path(Path::new("/home/u/vd-plugins"), Some("/a:/b".into())).unwrap(),
+            "/home/u/vd-plugins:/a:/b"
+        );
+    }
+}
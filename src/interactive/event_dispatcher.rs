@@ -1,112 +1,308 @@
-use std::path::{Path, PathBuf};
-
 use tokio::sync::mpsc;
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     cli::{self, args::Commands},
-    core::{plugin::Plugin, registry::Registry},
-    error::Result,
-    interactive::registry_snapshot::RegistrySnapshot,
-    utils::hash,
+    config_loader::{self, AppConfig},
+    core::{
+        lockfile,
+        plugin::{Action, Plugin},
+        registry::Registry,
+    },
+    error::{Result, VDPMError},
+    fs::{
+        paths::{
+            get_backups_folder_path, get_install_records_file_path, get_lock_file_path,
+            get_registry_file_path, get_trash_folder_path,
+        },
+        transaction::Transaction,
+    },
+    interactive::{
+        InteractiveEvent,
+        merge::{merge_edits, with_conflicts},
+        registry_snapshot::RegistrySnapshot,
+        revert_registry_file, write_registry_file,
+    },
+    sources::{InstallSource, git},
+    utils::{get_home_dir, hash},
 };
 
-#[derive(Debug)]
-struct PluginOperation {
-    command: Commands,
-    plugin: Plugin,
+#[derive(Debug, Clone)]
+pub(crate) struct PluginOperation {
+    pub command: Commands,
+    pub plugin: Plugin,
 }
 
 pub fn listen(
-    rx: mpsc::Receiver<RegistrySnapshot>,
+    rx: mpsc::Receiver<InteractiveEvent>,
     last_processed_registry_snapshot: RegistrySnapshot,
 ) {
     tokio::spawn(async move {
         debug!("we started reading the event line!");
         if let Err(e) = listen_registry_changes(rx, last_processed_registry_snapshot).await {
-            // @memedov: what do you think about coming up with reverting logic!
             error!("registry listener failed: {e}");
         }
     });
 }
 
-async fn listen_registry_changes(
-    mut rx: mpsc::Receiver<RegistrySnapshot>,
-    mut last_processed_registry_snapshot: RegistrySnapshot,
-) -> Result<()> {
-    while let Some(new_registry_snapshot) = rx.recv().await {
-        debug!("Got a content change message: {}", &new_registry_snapshot);
-        // TODO @memedov, if registry snapshot created_at < last message processed, we should simply skip the message
+/// Applies the edit of `new_registry_snapshot` to the installed plugins,
+/// which matched `last_processed_registry_snapshot`. Returns the snapshot the
+/// installed plugins match afterwards.
+async fn process_registry_edit(
+    last_processed_registry_snapshot: RegistrySnapshot,
+    new_registry_snapshot: RegistrySnapshot,
+) -> Result<RegistrySnapshot> {
+    if !new_registry_snapshot.supersedes(&last_processed_registry_snapshot) {
         debug!(
-            "old hash: {}, new hash: {}",
-            &last_processed_registry_snapshot.hash, &new_registry_snapshot.hash
+            "Skipping snapshot({}), it is stale or has nothing new after snapshot({})",
+            new_registry_snapshot.sequence, last_processed_registry_snapshot.sequence
         );
+        return Ok(last_processed_registry_snapshot);
+    }
+    merge_and_apply(last_processed_registry_snapshot, new_registry_snapshot).await
+}
 
-        if new_registry_snapshot.hash != last_processed_registry_snapshot.hash {
-            let operations: Vec<PluginOperation> = generate_operations(
-                &last_processed_registry_snapshot.registry,
-                &new_registry_snapshot.registry,
-            );
+/// Plugins may have changed on disk since the sheet was processed last, so
+/// the edits of the sheet are three-way merged with the installed plugins
+/// before they are applied. Conflicting edits are dropped and reported in the
+/// `error` column, and the sheet is rewritten whenever it does not show the
+/// installed plugins.
+async fn merge_and_apply(
+    last_processed_registry_snapshot: RegistrySnapshot,
+    new_registry_snapshot: RegistrySnapshot,
+) -> Result<RegistrySnapshot> {
+    let installed_registry = match Registry::generate().await {
+        Ok(installed_registry) => installed_registry,
+        Err(e) => {
+            warn!("Installed plugins could not be read, assuming the last sheet: {e}");
+            last_processed_registry_snapshot.registry.clone()
+        }
+    };
+    let merge = merge_edits(
+        &last_processed_registry_snapshot.registry,
+        &new_registry_snapshot.registry,
+        &installed_registry,
+    );
+    for conflict in &merge.conflicts {
+        warn!("Conflicting registry edit: {conflict}");
+    }
 
-            dispatch_operation(operations).await?;
-            last_processed_registry_snapshot = new_registry_snapshot;
+    let operations: Vec<PluginOperation> =
+        generate_operations(&installed_registry, &merge.registry);
+    let failed_plugins: Vec<String> = operations
+        .iter()
+        .map(|operation| operation.plugin.name.clone())
+        .collect();
+    // edits without an operation, like a changed read-only column, are reverted
+    let registry = if operations.is_empty() {
+        installed_registry
+    } else {
+        if let Err(e) = dispatch_operation(operations).await {
+            error!("Registry changes could not be applied: {e}");
+            return revert_registry_file(
+                Some(&last_processed_registry_snapshot.registry),
+                &e.to_string(),
+                &failed_plugins,
+                &merge.conflicts,
+            )
+            .await;
+        }
+        Registry::generate().await.unwrap_or_else(|e| {
+            warn!("Installed plugins could not be read, assuming the edit: {e}");
+            merge.registry
+        })
+    };
+
+    let registry = with_conflicts(registry, &merge.conflicts).with_details()?;
+    if hash(&registry)? == new_registry_snapshot.hash {
+        return Ok(new_registry_snapshot);
+    }
+    info!("Registry file is refreshed with the installed plugins!");
+    write_registry_file(&registry).await
+}
+
+/// Shows plugins changed outside of the sheet in it, applying the edits of
+/// the sheet that were not processed yet.
+async fn process_plugins_change(
+    last_processed_registry_snapshot: RegistrySnapshot,
+) -> Result<RegistrySnapshot> {
+    let sheet = match Registry::from_file(&get_registry_file_path()?).await {
+        Ok(sheet) => sheet,
+        Err(e) => {
+            warn!("Registry file could not be read, its edits are dropped: {e}");
+            last_processed_registry_snapshot.registry.clone()
+        }
+    };
+    merge_and_apply(
+        last_processed_registry_snapshot,
+        RegistrySnapshot::new(sheet)?,
+    )
+    .await
+}
+
+async fn listen_registry_changes(
+    mut rx: mpsc::Receiver<InteractiveEvent>,
+    mut last_processed_registry_snapshot: RegistrySnapshot,
+) -> Result<()> {
+    while let Some(event) = rx.recv().await {
+        let processed = match event {
+            InteractiveEvent::RegistryEdited(new_registry_snapshot) => {
+                debug!("Got a content change message: {}", &new_registry_snapshot);
+                process_registry_edit(
+                    last_processed_registry_snapshot.clone(),
+                    new_registry_snapshot,
+                )
+                .await
+            }
+            InteractiveEvent::PluginsChanged => {
+                process_plugins_change(last_processed_registry_snapshot.clone()).await
+            }
+        };
+        // a failed event must not stop the listener, the next one is merged
+        // against the last sheet that was processed
+        match processed {
+            Ok(registry_snapshot) => last_processed_registry_snapshot = registry_snapshot,
+            Err(e) => error!(
+                "Processing the event failed, keeping snapshot({}): {e}",
+                last_processed_registry_snapshot.sequence
+            ),
         }
     }
     Ok(())
 }
 
-fn generate_operations(old_registry: &Registry, new_registry: &Registry) -> Vec<PluginOperation> {
+/// The `install` argument reinstalling `plugin` exactly: its recorded source,
+/// pinned to the recorded commit for git sources and to the `version` column
+/// for registry sources, so editing it moves the plugin to that version.
+fn install_source(plugin: &Plugin) -> String {
+    match (&plugin.source, &plugin.commit) {
+        (Some(source), Some(commit)) if source.starts_with("git+") => {
+            let (url, _) = git::split_ref(source.trim_start_matches("git+"));
+            format!("git+{}@{}", url, commit)
+        }
+        (Some(source), _) => match InstallSource::parse(source) {
+            Ok(InstallSource::Index {
+                registry,
+                name,
+                version,
+            }) => InstallSource::Index {
+                registry,
+                name,
+                version: plugin.version.clone().or(version),
+            }
+            .to_string(),
+            _ => source.clone(),
+        },
+        (None, _) => plugin.name.clone(),
+    }
+}
+
+fn install_operations(plugin: &Plugin) -> Vec<PluginOperation> {
+    let mut operations = vec![PluginOperation {
+        command: Commands::Install {
+            name: install_source(plugin),
+        },
+        plugin: plugin.clone(),
+    }];
+    if plugin.enabled {
+        operations.push(PluginOperation {
+            command: Commands::Enable {
+                name: plugin.name.clone(),
+            },
+            plugin: plugin.clone(),
+        });
+    }
+    // uninstalling drops the install record, a reinstall pins the plugin again
+    if plugin.pinned {
+        operations.push(PluginOperation {
+            command: Commands::Pin {
+                name: plugin.name.clone(),
+            },
+            plugin: plugin.clone(),
+        });
+    }
+    operations
+}
+
+/// The operation of an `action` that runs after the plugin is installed.
+fn action_operation(plugin: &Plugin) -> Option<PluginOperation> {
+    let name = plugin.name.clone();
+    let command = match plugin.action? {
+        Action::Update => Commands::Update {
+            name: Some(name),
+            all: false,
+        },
+        Action::Pin => Commands::Pin { name },
+        Action::Unpin => Commands::Unpin { name },
+        Action::Install | Action::Uninstall => return None,
+    };
+    Some(PluginOperation {
+        command,
+        plugin: plugin.clone(),
+    })
+}
+
+/// Whether `new_plugin` asks for a different build of the plugin than the
+/// installed `old_plugin`, which can only be reached by reinstalling it.
+fn needs_reinstall(old_plugin: &Plugin, new_plugin: &Plugin) -> bool {
+    new_plugin.source.is_some()
+        && (new_plugin.source != old_plugin.source
+            || new_plugin.commit != old_plugin.commit
+            || new_plugin.version != old_plugin.version
+            || (new_plugin.sha256.is_some() && new_plugin.sha256 != old_plugin.sha256))
+}
+
+pub(crate) fn generate_operations(
+    old_registry: &Registry,
+    new_registry: &Registry,
+) -> Vec<PluginOperation> {
     let mut operations = Vec::new();
 
     for (plugin_name, old_plugin) in &old_registry.plugins {
-        let op = if !new_registry.plugins.contains_key(plugin_name) {
-            Some(PluginOperation {
-                command: Commands::Uninstall {
+        let uninstall = PluginOperation {
+            command: Commands::Uninstall {
+                name: plugin_name.clone(),
+            },
+            plugin: old_plugin.clone(),
+        };
+
+        let Some(new_plugin) = new_registry.plugins.get(plugin_name) else {
+            operations.push(uninstall);
+            continue;
+        };
+
+        if new_plugin.action == Some(Action::Uninstall) {
+            operations.push(uninstall);
+            continue;
+        }
+        if new_plugin.action == Some(Action::Install) || needs_reinstall(old_plugin, new_plugin) {
+            operations.push(uninstall);
+            operations.extend(install_operations(new_plugin));
+        } else if new_plugin.enabled && !old_plugin.enabled {
+            operations.push(PluginOperation {
+                command: Commands::Enable {
                     name: plugin_name.clone(),
                 },
                 plugin: old_plugin.clone(),
-            })
-        } else {
-            let new_plugin = &new_registry.plugins[plugin_name];
-
-            if new_plugin.enabled && !old_plugin.enabled {
-                Some(PluginOperation {
-                    command: Commands::Enable {
-                        name: plugin_name.clone(),
-                    },
-                    plugin: old_plugin.clone(),
-                })
-            } else if !new_plugin.enabled && old_plugin.enabled {
-                Some(PluginOperation {
-                    command: Commands::Disable {
-                        name: plugin_name.clone(),
-                    },
-                    plugin: old_plugin.clone(),
-                })
-            } else {
-                None
-            }
-        };
-
-        if let Some(op) = op {
-            operations.push(op);
+            });
+        } else if !new_plugin.enabled && old_plugin.enabled {
+            operations.push(PluginOperation {
+                command: Commands::Disable {
+                    name: plugin_name.clone(),
+                    comment: false,
+                },
+                plugin: old_plugin.clone(),
+            });
         }
+        operations.extend(action_operation(new_plugin));
     }
 
     for (plugin_name, new_plugin) in &new_registry.plugins {
-        let op = if !old_registry.plugins.contains_key(plugin_name) {
-            Some(PluginOperation {
-                command: Commands::Install {
-                    name: plugin_name.clone(),
-                },
-                plugin: new_plugin.clone(),
-            })
-        } else {
-            None
-        };
-
-        if let Some(op) = op {
-            operations.push(op);
+        if !old_registry.plugins.contains_key(plugin_name)
+            && new_plugin.action != Some(Action::Uninstall)
+        {
+            operations.extend(install_operations(new_plugin));
+            operations.extend(action_operation(new_plugin));
         }
     }
 
@@ -114,13 +310,217 @@ fn generate_operations(old_registry: &Registry, new_registry: &Registry) -> Vec<
 }
 
 async fn dispatch_operation(plugin_operations: Vec<PluginOperation>) -> Result<()> {
-    for operation in plugin_operations {
-        let operation_result = cli::run(&operation.command).await?;
-        debug!(
-            "Operation({}) is succesfully finished with result({})",
-            &operation.command, operation_result
-        )
+    apply_operations(&plugin_operations).await?;
+    lockfile::update().await
+}
+
+/// Runs `operations` as one transaction: .visidatarc, the install records,
+/// the lock file, the entries of the trash, backups and plugin folders and the
+/// files of every plugin involved are snapshotted first and restored when an operation fails, and
+/// the error names that operation.
+pub(crate) async fn apply_operations(operations: &[PluginOperation]) -> Result<()> {
+    run_operations(operations).await.map(|_transaction| ())
+}
+
+/// Rolls back a failed batch, the error says whether the rollback succeeded.
+pub(crate) fn rollback_batch(transaction: Transaction, failed: String, e: VDPMError) -> VDPMError {
+    match transaction.rollback() {
+        Ok(()) => VDPMError::OperationFailed(failed, Box::new(e)),
+        Err(rollback_error) => VDPMError::RollbackFailed(failed, Box::new(e), rollback_error),
     }
+}
 
-    Ok(())
+/// Runs the operations as one batch, rolling all of them back when one fails.
+/// The transaction of a successful batch is handed back still open, so the
+/// caller can check the result and roll the batch back as well.
+pub(crate) async fn run_operations(operations: &[PluginOperation]) -> Result<Transaction> {
+    let config: AppConfig = config_loader::load_or_create()?;
+    let plugin_folder = get_home_dir().join(&config.settings.plugin_folder);
+    let state_files = [
+        get_home_dir().join(&config.settings.rc_file),
+        get_install_records_file_path()?,
+        get_lock_file_path()?,
+    ];
+    let mut plugin_names: Vec<String> = operations
+        .iter()
+        .map(|operation| operation.plugin.name.clone())
+        .collect();
+    plugin_names.sort();
+    plugin_names.dedup();
+
+    // uninstall and update move the plugin into a new trash or backups entry,
+    // and a url, git or local install names the plugin after its file or
+    // package rather than the row
+    let entry_folders = [
+        get_trash_folder_path()?,
+        get_backups_folder_path()?,
+        plugin_folder.clone(),
+    ];
+    let transaction =
+        Transaction::begin(&state_files, &entry_folders, &plugin_folder, &plugin_names)
+            .map_err(|e| VDPMError::PluginFileError("Failed to snapshot plugins".into(), e))?;
+
+    for operation in operations {
+        match Box::pin(cli::dispatch(&operation.command)).await {
+            Ok(operation_result) => debug!(
+                "Operation({}) on plugin({:?}) is succesfully finished with result({})",
+                &operation.command, &operation.plugin, operation_result
+            ),
+            Err(e) => {
+                error!(
+                    "Operation({}) failed, rolling back the batch: {}",
+                    operation.command, e
+                );
+                return Err(rollback_batch(
+                    transaction,
+                    operation.command.to_string(),
+                    e,
+                ));
+            }
+        }
+    }
+
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::registry;
+
+    fn plugin(name: &str, enabled: bool, commit: Option<&str>) -> Plugin {
+        Plugin {
+            source: Some(format!("git+file:///srv/{}.git@main", name)),
+            commit: commit.map(String::from),
+            ..crate::core::test_fixtures::plugin(name, enabled)
+        }
+    }
+
+    #[test]
+    fn test_generate_operations() {
+        let old_registry = registry([
+            plugin("kept", false, Some("a")),
+            plugin("removed", true, Some("a")),
+            plugin("moved", true, Some("a")),
+        ]);
+        let new_registry = registry([
+            plugin("kept", true, Some("a")),
+            plugin("moved", true, Some("b")),
+            plugin("added", true, Some("c")),
+        ]);
+
+        let commands: Vec<String> = generate_operations(&old_registry, &new_registry)
+            .into_iter()
+            .map(|operation| operation.command.to_string())
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                "enable kept",
+                "uninstall moved",
+                "install git+file:///srv/moved.git@b",
+                "enable moved",
+                "uninstall removed",
+                "install git+file:///srv/added.git@c",
+                "enable added",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_operations_for_actions() {
+        let with_action = |name: &str, action: Action| Plugin {
+            action: Some(action),
+            ..plugin(name, true, Some("a"))
+        };
+        // uninstalling drops the pin, the reinstall pins the plugin again
+        let reinstalled = Plugin {
+            pinned: true,
+            ..plugin("reinstalled", true, Some("a"))
+        };
+        let old_registry = registry([
+            plugin("updated", true, Some("a")),
+            plugin("pinned", true, Some("a")),
+            plugin("removed", true, Some("a")),
+            reinstalled.clone(),
+        ]);
+        let new_registry = registry([
+            with_action("updated", Action::Update),
+            with_action("pinned", Action::Pin),
+            with_action("removed", Action::Uninstall),
+            Plugin {
+                action: Some(Action::Install),
+                ..reinstalled
+            },
+            with_action("added", Action::Pin),
+        ]);
+
+        let commands: Vec<String> = generate_operations(&old_registry, &new_registry)
+            .into_iter()
+            .map(|operation| operation.command.to_string())
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                "pin pinned",
+                "uninstall reinstalled",
+                "install git+file:///srv/reinstalled.git@a",
+                "enable reinstalled",
+                "pin reinstalled",
+                "uninstall removed",
+                "update updated",
+                "install git+file:///srv/added.git@a",
+                "enable added",
+                "pin added",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_version_edit_reinstalls_from_the_registry() {
+        let installed = Plugin {
+            source: Some("main/hello@0.2.0".into()),
+            version: Some("0.2.0".into()),
+            ..crate::core::test_fixtures::plugin("hello", false)
+        };
+        let edited = Plugin {
+            version: Some("0.3.0".into()),
+            ..installed.clone()
+        };
+
+        let commands: Vec<String> =
+            generate_operations(&registry([installed]), &registry([edited]))
+                .into_iter()
+                .map(|operation| operation.command.to_string())
+                .collect();
+        assert_eq!(
+            commands,
+            vec!["uninstall hello", "install main/hello@0.3.0"]
+        );
+    }
+
+    #[test]
+    fn test_sha256_mismatch_reinstalls() {
+        let with_sha256 = |sha256: Option<&str>| Plugin {
+            sha256: sha256.map(String::from),
+            ..plugin("hello", true, Some("a"))
+        };
+
+        let commands = |installed: Plugin, locked: Plugin| -> Vec<String> {
+            generate_operations(&registry([installed]), &registry([locked]))
+                .into_iter()
+                .map(|operation| operation.command.to_string())
+                .collect()
+        };
+        assert!(commands(with_sha256(Some("aa")), with_sha256(Some("aa"))).is_empty());
+        assert!(commands(with_sha256(Some("aa")), with_sha256(None)).is_empty());
+        assert_eq!(
+            commands(with_sha256(Some("bb")), with_sha256(Some("aa"))),
+            vec![
+                "uninstall hello",
+                "install git+file:///srv/hello.git@a",
+                "enable hello",
+            ]
+        );
+    }
 }
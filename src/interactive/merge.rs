@@ -0,0 +1,174 @@
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// A plugin both edited in the sheet and changed on disk since the sheet was
+/// last processed. The disk wins and the edit is dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub plugin_name: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "plugin({}) {}", self.plugin_name, self.reason)
+    }
+}
+
+#[derive(Debug)]
+pub struct Merge {
+    pub registry: Registry,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// The columns of the plugin a user can edit. The others describe the
+/// installed plugin, and `error` only reports on the last edit.
+fn editable(plugin: Option<&Plugin>) -> Option<Plugin> {
+    plugin.map(|plugin| Plugin {
+        error: None,
+        ..plugin.with_read_only_of(None)
+    })
+}
+
+fn conflict_reason(sheet: Option<&Plugin>, installed: Option<&Plugin>) -> &'static str {
+    match (sheet, installed) {
+        (Some(_), None) => "was removed outside of the sheet, the edit is dropped",
+        (None, Some(_)) => "was changed outside of the sheet, it is not uninstalled",
+        _ => "was changed outside of the sheet, the edit is dropped",
+    }
+}
+
+/// `registry` with each conflict reported in the `error` column of its plugin.
+pub fn with_conflicts(registry: Registry, conflicts: &[Conflict]) -> Registry {
+    conflicts.iter().fold(registry, |registry, conflict| {
+        registry.with_error(
+            &conflict.to_string(),
+            std::slice::from_ref(&conflict.plugin_name),
+        )
+    })
+}
+
+/// Three-way merges the edits of `sheet` with the `installed` plugins, both
+/// compared to `base`, the sheet the installed plugins last matched. Plugins
+/// only edited in the sheet take the edit, plugins only changed on disk stay
+/// as installed, and plugins changed differently on both sides are conflicts.
+pub fn merge_edits(base: &Registry, sheet: &Registry, installed: &Registry) -> Merge {
+    let mut registry = installed.clone();
+    let mut conflicts: Vec<Conflict> = Vec::new();
+    let plugin_names: BTreeSet<&String> = base
+        .plugins
+        .keys()
+        .chain(sheet.plugins.keys())
+        .chain(installed.plugins.keys())
+        .collect();
+
+    for plugin_name in plugin_names {
+        let installed_row = installed.plugins.get(plugin_name);
+        let base_plugin = editable(base.plugins.get(plugin_name));
+        let sheet_plugin = editable(sheet.plugins.get(plugin_name));
+        let installed_plugin = editable(installed_row);
+
+        if sheet_plugin == base_plugin || sheet_plugin == installed_plugin {
+            continue;
+        }
+        if installed_plugin != base_plugin {
+            conflicts.push(Conflict {
+                plugin_name: plugin_name.clone(),
+                reason: conflict_reason(sheet_plugin.as_ref(), installed_plugin.as_ref()).into(),
+            });
+            continue;
+        }
+        match sheet_plugin {
+            Some(sheet_plugin) => registry.plugins.insert(
+                plugin_name.clone(),
+                sheet_plugin.with_read_only_of(installed_row),
+            ),
+            None => registry.plugins.remove(plugin_name),
+        };
+    }
+
+    Merge {
+        registry,
+        conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::{plugin, registry};
+
+    #[test]
+    fn test_merge_edits() {
+        let base = registry([
+            plugin("hello", true),
+            plugin("old", true),
+            plugin("gone", true),
+            plugin("busy", false),
+        ]);
+        // `old` was removed and `dropped_in` copied in by hand, `gone` was
+        // removed and `busy` enabled outside of the sheet as well
+        let installed = registry([
+            plugin("hello", true),
+            plugin("dropped_in", false),
+            plugin("busy", true),
+        ]);
+        // meanwhile `hello` was disabled, `new` added, `gone` toggled and
+        // `busy` removed in the sheet
+        let sheet = registry([
+            plugin("hello", false),
+            plugin("old", true),
+            plugin("gone", false),
+            plugin("new", true),
+        ]);
+
+        let merge = merge_edits(&base, &sheet, &installed);
+        assert_eq!(
+            merge.registry.plugins.keys().collect::<Vec<_>>(),
+            ["busy", "dropped_in", "hello", "new"]
+        );
+        assert!(!merge.registry.plugins["hello"].enabled);
+        assert!(merge.registry.plugins["busy"].enabled);
+        assert_eq!(
+            merge
+                .conflicts
+                .iter()
+                .map(|conflict| conflict.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "plugin(busy) was changed outside of the sheet, it is not uninstalled",
+                "plugin(gone) was removed outside of the sheet, the edit is dropped",
+            ]
+        );
+
+        // edits of read-only columns are ignored
+        let mut sheet = base.clone();
+        if let Some(hello) = sheet.plugins.get_mut("hello") {
+            hello.size = Some(1);
+            hello.installed_at = Some("yesterday".into());
+        }
+        let merge = merge_edits(&base, &sheet, &installed);
+        assert_eq!(
+            merge.registry.plugins.keys().collect::<Vec<_>>(),
+            ["busy", "dropped_in", "hello"]
+        );
+        assert_eq!(merge.registry.plugins["hello"], installed.plugins["hello"]);
+        assert!(merge.conflicts.is_empty());
+
+        // so are edits of the version of a pinned plugin
+        let pinned = Plugin {
+            version: Some("1.0".into()),
+            pinned: true,
+            ..plugin("hello", true)
+        };
+        let base = registry([pinned.clone()]);
+        let mut sheet = base.clone();
+        if let Some(hello) = sheet.plugins.get_mut("hello") {
+            hello.version = Some("2.0".into());
+        }
+        let merge = merge_edits(&base, &sheet, &base);
+        assert_eq!(merge.registry.plugins["hello"], pinned);
+    }
+}
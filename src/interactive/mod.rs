@@ -1,31 +1,50 @@
-use crate::error::Result;
+use crate::error::{Result, VDPMError};
 use crate::fs::paths::get_registry_file_path;
-use crate::utils::hash;
+use crate::utils::get_home_dir;
 use crate::{config_loader::AppConfig, core::registry::Registry};
 use notify::RecommendedWatcher;
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use tokio::sync::mpsc;
-use tracing::info;
-mod event_dispatcher;
+use tracing::{info, warn};
+pub mod companion;
+pub(crate) mod event_dispatcher;
+mod merge;
 pub mod registry_snapshot;
 mod watcher;
+use merge::{Conflict, with_conflicts};
 use registry_snapshot::RegistrySnapshot;
 
-pub async fn launch(app_config: AppConfig) -> Result<(Child, RecommendedWatcher)> {
+/// The folder VisiData imports `plugins` from.
+const VISIDATA_DIR: &str = ".visidata";
+
+/// What the event dispatcher reacts to while VisiData is open.
+#[derive(Debug)]
+pub enum InteractiveEvent {
+    /// The registry sheet was saved
+    RegistryEdited(RegistrySnapshot),
+    /// Plugins or .visidatarc were changed outside of the sheet
+    PluginsChanged,
+}
+
+pub async fn launch(app_config: AppConfig) -> Result<(Child, Vec<RecommendedWatcher>)> {
     info!("Launchin interactive mode!");
     let registry_file_path: PathBuf = get_registry_file_path()?;
-    let registry = Registry::generate().await?;
-    let last_processed_registry_snapshot = RegistrySnapshot {
-        hash: hash(&registry),
-        registry: registry.clone(),
-    };
+    let registry = Registry::generate().await?.with_details()?;
+    let last_processed_registry_snapshot = RegistrySnapshot::new(registry.clone())?;
 
     registry.to_file(&registry_file_path).await?;
 
-    let (tx, mut rx) = mpsc::channel::<RegistrySnapshot>(1);
+    let (tx, rx) = mpsc::channel::<InteractiveEvent>(1);
     info!("Before starting watching!");
-    let watcher: RecommendedWatcher = watcher::watch_file(&registry_file_path, tx.clone())?;
+    let watchers: Vec<RecommendedWatcher> = vec![
+        watcher::watch_file(&registry_file_path, tx.clone())?,
+        watcher::watch_plugins(
+            &get_home_dir().join(&app_config.settings.plugin_folder),
+            &get_home_dir().join(&app_config.settings.rc_file),
+            tx.clone(),
+        )?,
+    ];
 
     event_dispatcher::listen(rx, last_processed_registry_snapshot);
 
@@ -36,5 +55,77 @@ pub async fn launch(app_config: AppConfig) -> Result<(Child, RecommendedWatcher)
         .stderr(Stdio::inherit())
         .spawn()
         .expect("failed to start VisiData");
-    Ok((child, watcher))
+    Ok((child, watchers))
+}
+
+/// Opens the native vdpm sheet of the companion plugin in VisiData, which
+/// manages plugins through `vdpm rpc` instead of a CSV file.
+pub async fn launch_native(app_config: AppConfig) -> Result<Child> {
+    info!("Launching the native vdpm sheet!");
+    let plugin_folder = get_home_dir().join(&app_config.settings.plugin_folder);
+    companion::install(&plugin_folder).await?;
+    let vdpm = std::env::current_exe().map_err(|e| {
+        VDPMError::PluginFileError("Failed to locate the vdpm executable".into(), e)
+    })?;
+
+    let mut command = Command::new("vd");
+    let (companion_module, python_folder) =
+        companion::import_path(&plugin_folder, &get_home_dir().join(VISIDATA_DIR));
+    if let Some(python_folder) = python_folder {
+        command.env(
+            "PYTHONPATH",
+            companion::python_path(&python_folder, std::env::var_os("PYTHONPATH"))?,
+        );
+    }
+    let child = command
+        .arg("--imports")
+        .arg(format!("plugins {}", companion_module))
+        .arg("--vdpm-command")
+        .arg(format!(
+            "{} rpc",
+            companion::shell_quote(&vdpm.to_string_lossy())
+        ))
+        .arg("--filetype")
+        .arg("vdpm")
+        .arg(&plugin_folder)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .expect("failed to start VisiData");
+    Ok(child)
+}
+
+/// Rewrites the registry file after an edit could not be applied, so VisiData
+/// reloads a truthful sheet with `error` in it instead of the rejected edit.
+/// The sheet shows the plugins actually installed, or `last_registry` when
+/// those can not be read, and the `conflicts` of the edit as well.
+pub(crate) async fn revert_registry_file(
+    last_registry: Option<&Registry>,
+    error: &str,
+    failed_plugins: &[String],
+    conflicts: &[Conflict],
+) -> Result<RegistrySnapshot> {
+    let registry = match (Registry::generate().await, last_registry) {
+        (Ok(registry), _) => registry,
+        (Err(e), Some(last_registry)) => {
+            warn!("Installed plugins could not be read, reverting to the last sheet: {e}");
+            last_registry.clone()
+        }
+        (Err(e), None) => return Err(e),
+    };
+
+    let registry =
+        with_conflicts(registry.with_error(error, failed_plugins), conflicts).with_details()?;
+    let registry = write_registry_file(&registry).await?;
+    info!("Registry file is reverted because of: {}", error);
+    Ok(registry)
+}
+
+/// Writes `registry` as the sheet and returns it as the watcher reads it
+/// back, so the write itself is dropped as a duplicate instead of applied.
+pub(crate) async fn write_registry_file(registry: &Registry) -> Result<RegistrySnapshot> {
+    let registry_file_path = get_registry_file_path()?;
+    registry.to_file(&registry_file_path).await?;
+    RegistrySnapshot::new(Registry::from_file(&registry_file_path).await?)
 }
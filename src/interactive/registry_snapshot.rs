@@ -1,19 +1,70 @@
 use crate::core::registry::Registry;
+use crate::error::Result;
+use crate::utils::hash;
+use chrono::{DateTime, Local};
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-#[derive(Debug)]
+/// Snapshots are numbered in the order they are taken, across all watchers.
+static NEXT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
 pub struct RegistrySnapshot {
     pub registry: Registry,
-    pub hash: u64,
-    // TODO @memedov lets add created_at to process idempotent
+    /// Stable digest of `registry`, see [`crate::utils::hash`]
+    pub hash: String,
+    /// Monotonic number of the snapshot, a higher one was taken later
+    pub sequence: u64,
+    pub created_at: DateTime<Local>,
+}
+
+impl RegistrySnapshot {
+    pub fn new(registry: Registry) -> Result<Self> {
+        Ok(RegistrySnapshot {
+            hash: hash(&registry)?,
+            registry,
+            sequence: NEXT_SEQUENCE.fetch_add(1, Ordering::SeqCst),
+            created_at: Local::now(),
+        })
+    }
+
+    /// Whether the snapshot has to be processed after `last_processed`: it
+    /// was taken later and the registry changed. Stale snapshots arriving out
+    /// of order and duplicates of the same content are dropped.
+    pub fn supersedes(&self, last_processed: &RegistrySnapshot) -> bool {
+        self.sequence > last_processed.sequence && self.hash != last_processed.hash
+    }
 }
 
 impl fmt::Display for RegistrySnapshot {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "RegistrySnapshot(hash: {}, registry: {:?})",
-            self.hash, self.registry
+            "RegistrySnapshot(sequence: {}, created_at: {}, hash: {}, registry: {:?})",
+            self.sequence,
+            self.created_at.to_rfc3339(),
+            self.hash,
+            self.registry
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_fixtures::{plugin, registry};
+
+    #[test]
+    fn test_stale_and_duplicate_snapshots_are_dropped() {
+        let first = RegistrySnapshot::new(registry([plugin("hello", true)])).unwrap();
+        let stale = RegistrySnapshot::new(registry([plugin("hello", false)])).unwrap();
+        let latest = RegistrySnapshot::new(registry([plugin("hello", false)])).unwrap();
+        let duplicate = RegistrySnapshot::new(registry([plugin("hello", false)])).unwrap();
+        assert!(stale.sequence < latest.sequence);
+
+        assert!(latest.supersedes(&first));
+        assert!(!stale.supersedes(&latest));
+        assert!(!duplicate.supersedes(&latest));
+        assert!(!first.supersedes(&first));
+    }
+}
@@ -0,0 +1,94 @@
+'''Manage VisiData plugins with vdpm from a native sheet.
+
+Installed by `vdpm interactive --native`, which opens the sheet right away.
+Run `open-vdpm` to open it from any other sheet once the plugin is enabled.
+The sheet talks to `vdpm rpc` over JSON-RPC on stdin and stdout.
+'''
+
+import json
+import shlex
+import subprocess
+
+from visidata import vd, VisiData, BaseSheet, Sheet, TextSheet, ItemColumn
+
+vd.option('vdpm_command', 'vdpm rpc', 'command starting the vdpm JSON-RPC backend')
+
+
+class VdpmBackend:
+    '''A `vdpm rpc` process, started on the first call and kept running.'''
+
+    def __init__(self):
+        self.process = None
+        self.next_id = 0
+
+    def call(self, method, **params):
+        if self.process is None or self.process.poll() is not None:
+            self.process = subprocess.Popen(
+                shlex.split(vd.options.vdpm_command),
+                stdin=subprocess.PIPE,
+                stdout=subprocess.PIPE,
+                stderr=subprocess.DEVNULL,
+                text=True,
+            )
+
+        self.next_id += 1
+        request = {'jsonrpc': '2.0', 'id': self.next_id, 'method': method, 'params': params}
+        self.process.stdin.write(json.dumps(request) + '\n')
+        self.process.stdin.flush()
+
+        line = self.process.stdout.readline()
+        if not line:
+            vd.fail('vdpm backend exited')
+        response = json.loads(line)
+        if 'error' in response:
+            vd.fail(response['error']['message'])
+        return response['result']
+
+
+vd.vdpm_backend = VdpmBackend()
+
+
+class VdpmSheet(Sheet):
+    'Installed plugins, as `vdpm list` shows them.'
+    rowtype = 'plugins'  # rowdef: dict of a plugin, as vdpm serializes it
+    columns = [
+        ItemColumn('name'),
+        ItemColumn('enabled'),
+        ItemColumn('version'),
+        ItemColumn('pinned'),
+        ItemColumn('description', width=40),
+        ItemColumn('source'),
+        ItemColumn('installed_at'),
+        ItemColumn('size', type=int),
+        ItemColumn('signed_by'),
+    ]
+    nKeys = 1
+
+    def iterload(self):
+        yield from vd.vdpm_backend.call('list')['plugins']
+
+    def run_vdpm(self, method, **params):
+        result = vd.vdpm_backend.call(method, **params)
+        vd.status(f'vdpm {method} {params.get("name", "")} is done')
+        self.reload()
+        return result
+
+    def openRow(self, row):
+        'Explain the plugin: its docstring, keybindings, options and api.'
+        output = vd.vdpm_backend.call('explain', name=row['name'])['output']
+        return TextSheet(f'{row["name"]}_explained', source=output.splitlines())
+
+
+VdpmSheet.addCommand('a', 'vdpm-install', 'run_vdpm("install", name=input("install: "))', 'install a plugin from a registry, url, git or local path')
+VdpmSheet.addCommand('d', 'vdpm-uninstall', 'vd.confirm(f"uninstall {cursorRow[\'name\']}? ") and run_vdpm("uninstall", name=cursorRow["name"])', 'uninstall the plugin, `vdpm restore` brings it back')
+VdpmSheet.addCommand('e', 'vdpm-enable', 'run_vdpm("enable", name=cursorRow["name"])', 'import the plugin in .visidatarc')
+VdpmSheet.addCommand('x', 'vdpm-disable', 'run_vdpm("disable", name=cursorRow["name"])', 'remove the plugin import from .visidatarc')
+VdpmSheet.addCommand('X', 'vdpm-explain', 'vd.push(openRow(cursorRow))', 'explain the plugin')
+
+
+@VisiData.api
+def open_vdpm(vd, p):
+    return VdpmSheet('vdpm', source=p)
+
+
+BaseSheet.addCommand('', 'open-vdpm', 'vd.push(VdpmSheet("vdpm"))', 'open the vdpm sheet of installed plugins')
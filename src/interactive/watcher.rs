@@ -1,73 +1,331 @@
 use crate::core::registry::Registry;
 use crate::error::{Result, VDPMError};
 use crate::interactive::registry_snapshot::RegistrySnapshot;
-use crate::utils::hash;
+use crate::interactive::{InteractiveEvent, revert_registry_file};
 use notify::event::ModifyKind;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::sync::mpsc;
-use tracing::{error, info};
+use tokio::time::timeout;
+use tracing::{debug, error, info};
 
+/// Watches the folder of `file_path` and sends a snapshot of the file after
+/// every save, be it written in place, created or renamed over the old file.
 pub fn watch_file(
     file_path: &Path,
-    tx_snapshots: mpsc::Sender<RegistrySnapshot>,
+    tx_events: mpsc::Sender<InteractiveEvent>,
 ) -> Result<RecommendedWatcher> {
     let file_path = file_path.to_path_buf();
+    let folder_path = file_path.parent().ok_or_else(|| {
+        VDPMError::InvalidArguments(format!(
+            "registry file {} has no parent folder",
+            file_path.display()
+        ))
+    })?;
     let (tx_file_events, rx_file_events) = mpsc::channel::<notify::Result<Event>>(100);
 
     let mut watcher = notify::recommended_watcher(move |res| {
         let _ = tx_file_events.blocking_send(res);
     })?;
 
-    watcher.watch(&file_path, RecursiveMode::NonRecursive)?;
+    // Editors saving through a temporary file replace the registry file, and a
+    // watch on the file itself would end with the old inode. The folder watch
+    // keeps seeing the file however it is written, so it never has to be re-armed.
+    watcher.watch(folder_path, RecursiveMode::NonRecursive)?;
 
     tokio::spawn(async move {
-        process_events_loop(rx_file_events, file_path, tx_snapshots).await;
+        process_events_loop(rx_file_events, file_path, tx_events).await;
     });
 
     Ok(watcher)
 }
 
+/// VisiData saves the sheet with several writes, so a change is only read
+/// once the file was quiet for this long.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(200);
+
+fn is_registry_change(event_result: notify::Result<Event>, file_path: &Path) -> bool {
+    match event_result {
+        Ok(event) => {
+            matches!(
+                event.kind,
+                EventKind::Create(_)
+                    | EventKind::Modify(ModifyKind::Data(_))
+                    | EventKind::Modify(ModifyKind::Name(_))
+                    | EventKind::Modify(ModifyKind::Any)
+            ) && event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == file_path.file_name())
+        }
+        Err(e) => {
+            error!("Failed to watch registry file: {}", e);
+            false
+        }
+    }
+}
+
+/// Waits until no events arrived for `delay`. Returns false if the channel
+/// closed meanwhile.
+async fn wait_until_quiet(
+    rx_events: &mut mpsc::Receiver<notify::Result<Event>>,
+    delay: Duration,
+) -> bool {
+    loop {
+        match timeout(delay, rx_events.recv()).await {
+            Ok(Some(event_result)) => debug!("Debouncing file event: {:?}", event_result),
+            Ok(None) => return false,
+            Err(_) => return true,
+        }
+    }
+}
+
 async fn process_events_loop(
     mut rx_events: mpsc::Receiver<notify::Result<Event>>,
     file_path: PathBuf,
-    tx_snapshots: mpsc::Sender<RegistrySnapshot>,
+    tx_events: mpsc::Sender<InteractiveEvent>,
 ) {
     while let Some(event_result) = rx_events.recv().await {
-        if let Err(e) =
-            process_file_change(event_result, file_path.clone(), tx_snapshots.clone()).await
-        {
-            handle_error(e);
+        if !is_registry_change(event_result, &file_path) {
+            continue;
+        }
+        let open = wait_until_quiet(&mut rx_events, DEBOUNCE_DELAY).await;
+        if !file_path.exists() {
+            // renamed away or removed, the save that recreates it is picked up
+            debug!("Registry file {} is gone for now", file_path.display());
+        } else if let Err(e) = process_file_change(&file_path, &tx_events).await {
+            handle_error(e).await;
+        }
+        if !open {
+            break;
         }
     }
 }
 
-async fn process_file_change(
-    event_result: notify::Result<Event>,
-    file_path: PathBuf,
-    tx: mpsc::Sender<RegistrySnapshot>,
-) -> Result<()> {
-    let event = event_result?;
-    if let EventKind::Modify(ModifyKind::Data(_)) = event.kind {
-        let current_registry = Registry::from_file(&file_path).await?;
-        info!("Processing file change started!");
-        let current_registry_hash = hash(&current_registry);
-        let current_registry_snapshot = RegistrySnapshot {
-            registry: current_registry,
-            hash: current_registry_hash,
-        };
-        tx.send(current_registry_snapshot).await.map_err(|e| {
+async fn process_file_change(file_path: &Path, tx: &mpsc::Sender<InteractiveEvent>) -> Result<()> {
+    info!("Processing file change started!");
+    let current_registry_snapshot = RegistrySnapshot::new(Registry::from_file(file_path).await?)?;
+    tx.send(InteractiveEvent::RegistryEdited(current_registry_snapshot))
+        .await
+        .map_err(|e| {
             VDPMError::RegistryFileChangeHandlerError(
                 "Failed send message to registry change queue".into(),
                 e,
             )
         })?;
-    }
     Ok(())
 }
 
-fn handle_error(e: impl Display) {
+/// Watches the plugin folder and the folder of `rc_file`, so plugins copied
+/// in or imports edited by hand while VisiData is open reach the sheet too.
+pub fn watch_plugins(
+    plugin_folder: &Path,
+    rc_file: &Path,
+    tx_events: mpsc::Sender<InteractiveEvent>,
+) -> Result<RecommendedWatcher> {
+    let rc_folder = rc_file.parent().ok_or_else(|| {
+        VDPMError::InvalidArguments(format!("{} has no parent folder", rc_file.display()))
+    })?;
+    std::fs::create_dir_all(plugin_folder).map_err(|e| {
+        VDPMError::PluginFileError(format!("Failed to create {}", plugin_folder.display()), e)
+    })?;
+    let (tx_file_events, rx_file_events) = mpsc::channel::<notify::Result<Event>>(100);
+
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx_file_events.blocking_send(res);
+    })?;
+
+    watcher.watch(plugin_folder, RecursiveMode::Recursive)?;
+    watcher.watch(rc_folder, RecursiveMode::NonRecursive)?;
+
+    let plugin_folder = plugin_folder.to_path_buf();
+    let rc_file = rc_file.to_path_buf();
+    tokio::spawn(async move {
+        process_plugin_events_loop(rx_file_events, plugin_folder, rc_file, tx_events).await;
+    });
+
+    Ok(watcher)
+}
+
+fn is_plugins_change(
+    event_result: notify::Result<Event>,
+    plugin_folder: &Path,
+    rc_file: &Path,
+) -> bool {
+    match event_result {
+        Ok(event) => {
+            !matches!(
+                event.kind,
+                EventKind::Access(_) | EventKind::Modify(ModifyKind::Metadata(_))
+            ) && event.paths.iter().any(|path| {
+                // python writes bytecode next to the plugins whenever VisiData imports them
+                let is_plugin_file = path.starts_with(plugin_folder)
+                    && !path
+                        .components()
+                        .any(|component| component.as_os_str() == "__pycache__");
+                is_plugin_file || path.file_name() == rc_file.file_name()
+            })
+        }
+        Err(e) => {
+            error!("Failed to watch plugins: {}", e);
+            false
+        }
+    }
+}
+
+async fn process_plugin_events_loop(
+    mut rx_events: mpsc::Receiver<notify::Result<Event>>,
+    plugin_folder: PathBuf,
+    rc_file: PathBuf,
+    tx_events: mpsc::Sender<InteractiveEvent>,
+) {
+    while let Some(event_result) = rx_events.recv().await {
+        if !is_plugins_change(event_result, &plugin_folder, &rc_file) {
+            continue;
+        }
+        let open = wait_until_quiet(&mut rx_events, DEBOUNCE_DELAY).await;
+        info!("Plugins are changed outside of the registry sheet!");
+        if tx_events
+            .send(InteractiveEvent::PluginsChanged)
+            .await
+            .is_err()
+            || !open
+        {
+            break;
+        }
+    }
+}
+
+/// A registry file that can not be processed, e.g. an edit VisiData saved
+/// with an invalid value, is reverted so the sheet matches the plugins again.
+async fn handle_error(e: impl Display) {
     error!("Failed to process file change because of {}!", e);
-    // TODO: @memedov our file changed process failed, shall we revert the file?
+    if let Err(revert_error) = revert_registry_file(None, &e.to_string(), &[], &[]).await {
+        error!("Failed to revert registry file: {}", revert_error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, MetadataKind, RemoveKind, RenameMode};
+
+    fn content_change() -> notify::Result<Event> {
+        Ok(Event::new(EventKind::Modify(ModifyKind::Data(
+            DataChange::Content,
+        ))))
+    }
+
+    #[test]
+    fn test_is_registry_change() {
+        let file_path = Path::new("/home/user/.config/vdpm/plugins.csv");
+        let event = |kind, path: &str| Ok(Event::new(kind).add_path(PathBuf::from(path)));
+
+        assert!(is_registry_change(
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/home/user/.config/vdpm/plugins.csv"
+            ),
+            file_path
+        ));
+        assert!(is_registry_change(
+            event(
+                EventKind::Create(CreateKind::File),
+                "/home/user/.config/vdpm/plugins.csv"
+            ),
+            file_path
+        ));
+        assert!(is_registry_change(
+            Ok(
+                Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+                    .add_path(PathBuf::from("/home/user/.config/vdpm/.plugins.csv.swp"))
+                    .add_path(PathBuf::from("/home/user/.config/vdpm/plugins.csv"))
+            ),
+            file_path
+        ));
+        assert!(!is_registry_change(
+            event(
+                EventKind::Modify(ModifyKind::Metadata(MetadataKind::Any)),
+                "/home/user/.config/vdpm/plugins.csv"
+            ),
+            file_path
+        ));
+        assert!(!is_registry_change(
+            event(
+                EventKind::Remove(RemoveKind::File),
+                "/home/user/.config/vdpm/plugins.csv"
+            ),
+            file_path
+        ));
+        assert!(!is_registry_change(
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/home/user/.config/vdpm/vdpm.lock"
+            ),
+            file_path
+        ));
+    }
+
+    #[test]
+    fn test_is_plugins_change() {
+        let plugin_folder = Path::new("/home/user/.visidata/plugins");
+        let rc_file = Path::new("/home/user/.visidatarc");
+        let event = |kind, path: &str| Ok(Event::new(kind).add_path(PathBuf::from(path)));
+
+        assert!(is_plugins_change(
+            event(
+                EventKind::Create(CreateKind::File),
+                "/home/user/.visidata/plugins/hello.py"
+            ),
+            plugin_folder,
+            rc_file
+        ));
+        assert!(is_plugins_change(
+            event(
+                EventKind::Remove(RemoveKind::Folder),
+                "/home/user/.visidata/plugins/pkg"
+            ),
+            plugin_folder,
+            rc_file
+        ));
+        assert!(is_plugins_change(
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/home/user/.visidatarc"
+            ),
+            plugin_folder,
+            rc_file
+        ));
+        assert!(!is_plugins_change(
+            event(
+                EventKind::Create(CreateKind::File),
+                "/home/user/.visidata/plugins/__pycache__/hello.cpython-312.pyc"
+            ),
+            plugin_folder,
+            rc_file
+        ));
+        assert!(!is_plugins_change(
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/home/user/.bashrc"
+            ),
+            plugin_folder,
+            rc_file
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_quiet_drains_bursts() {
+        let (tx, mut rx) = mpsc::channel(10);
+        for _ in 0..3 {
+            tx.send(content_change()).await.unwrap();
+        }
+        assert!(wait_until_quiet(&mut rx, Duration::from_millis(20)).await);
+        assert!(rx.try_recv().is_err());
+
+        drop(tx);
+        assert!(!wait_until_quiet(&mut rx, Duration::from_millis(20)).await);
+    }
 }
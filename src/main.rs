@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Child;
 
 use clap::Parser;
@@ -12,11 +12,14 @@ mod error;
 mod fs;
 mod interactive;
 mod logger;
+mod rpc;
+mod sources;
 mod utils;
 
 use crate::config_loader::AppConfig;
 use crate::error::Result;
 use crate::fs::operations::create_visidata_rc;
+use crate::fs::paths::get_socket_path;
 use cli::args::Cli;
 
 #[tokio::main]
@@ -32,19 +35,31 @@ async fn main() -> Result<()> {
         config
     );
 
-    create_visidata_rc(&Path::new(&config.settings.rc_file)).await?;
+    create_visidata_rc(Path::new(&config.settings.rc_file)).await?;
 
     let cli = Cli::parse();
 
     match cli.command {
-        cli::args::Commands::Interactive => {
+        cli::args::Commands::Interactive { native: true } => {
+            let mut vdpm_sheet_process: Child = interactive::launch_native(config).await?;
+            vdpm_sheet_process.wait().expect("VisiData process failed!");
+        }
+        cli::args::Commands::Interactive { native: false } => {
             info!("Starting interactive VDPM!");
-            let (mut interactive_process, _watcher): (Child, RecommendedWatcher) =
+            let (mut interactive_process, _watchers): (Child, Vec<RecommendedWatcher>) =
                 interactive::launch(config).await?;
             interactive_process
                 .wait()
                 .expect("VisiData process failed!");
         }
+        cli::args::Commands::Rpc => rpc::serve_stdio().await?,
+        cli::args::Commands::Serve { socket } => {
+            let socket_path: PathBuf = match socket {
+                Some(socket_path) => socket_path,
+                None => get_socket_path()?,
+            };
+            rpc::serve_socket(&socket_path).await?;
+        }
         command => {
             let result: Table = cli::run(&command).await?;
             println!("{}", result);
@@ -0,0 +1,382 @@
+use crate::cli::commands::search;
+use crate::cli::{self, args::Commands};
+use crate::core::plugin::Plugin;
+use crate::core::registry::Registry;
+use crate::core::search::SearchQuery;
+use crate::error::{Result, VDPMError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs::Permissions;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Raised by the command itself, like a plugin that is not installed
+const COMMAND_FAILED: i64 = -32000;
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Requests without an id are notifications, they get no response
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct NameParams {
+    name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SearchParams {
+    query: Option<String>,
+    tag: Option<String>,
+    author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DisableParams {
+    name: String,
+    #[serde(default)]
+    comment: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl Response {
+    fn new(id: Value, result: std::result::Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Response {
+            jsonrpc: "2.0",
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+fn rpc_error(code: i64, message: impl ToString) -> RpcError {
+    RpcError {
+        code,
+        message: message.to_string(),
+    }
+}
+
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> std::result::Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| rpc_error(INVALID_PARAMS, e))
+}
+
+/// The command behind a method.
+fn command(method: &str, params_value: Value) -> std::result::Result<Commands, RpcError> {
+    Ok(match method {
+        "install" => Commands::Install {
+            name: params::<NameParams>(params_value)?.name,
+        },
+        "uninstall" => Commands::Uninstall {
+            name: params::<NameParams>(params_value)?.name,
+        },
+        "enable" => Commands::Enable {
+            name: params::<NameParams>(params_value)?.name,
+        },
+        "disable" => {
+            let DisableParams { name, comment } = params(params_value)?;
+            Commands::Disable { name, comment }
+        }
+        "explain" => Commands::Explain {
+            name: params::<NameParams>(params_value)?.name,
+        },
+        _ => {
+            return Err(rpc_error(
+                METHOD_NOT_FOUND,
+                format!("unknown method {}", method),
+            ));
+        }
+    })
+}
+
+/// Commands run one at a time, clients of `vdpm serve` may call concurrently.
+static COMMAND_LOCK: Mutex<()> = Mutex::const_new(());
+
+async fn installed_plugins() -> std::result::Result<Vec<Plugin>, RpcError> {
+    let registry: Registry = Registry::generate()
+        .await
+        .and_then(Registry::with_details)
+        .map_err(|e| rpc_error(COMMAND_FAILED, e))?;
+    Ok(registry.plugins.into_values().collect())
+}
+
+/// `list` and `search` answer with data. Every other method answers with the
+/// table the matching command prints, and with the installed plugins after it
+/// if it changed them.
+async fn call(method: &str, params_value: Value) -> std::result::Result<Value, RpcError> {
+    let _command_guard = COMMAND_LOCK.lock().await;
+    match method {
+        "list" => Ok(json!({ "plugins": installed_plugins().await? })),
+        "search" => {
+            let SearchParams { query, tag, author } = params(params_value)?;
+            let results = search::find(&SearchQuery {
+                text: query,
+                tag,
+                author,
+            })
+            .await
+            .map_err(|e| rpc_error(COMMAND_FAILED, e))?;
+            Ok(json!({ "results": results }))
+        }
+        method => {
+            let command = command(method, params_value)?;
+            info!("Running {} for a JSON-RPC client!", command);
+            let output = cli::run(&command)
+                .await
+                .map_err(|e| rpc_error(COMMAND_FAILED, e))?
+                .to_string();
+            if command.changes_plugins() {
+                Ok(json!({ "output": output, "plugins": installed_plugins().await? }))
+            } else {
+                Ok(json!({ "output": output }))
+            }
+        }
+    }
+}
+
+/// Answers one JSON-RPC 2.0 request, `None` for notifications.
+pub async fn handle(request: &str) -> Option<String> {
+    debug!("JSON-RPC request: {}", request);
+    let response = match serde_json::from_str::<Value>(request) {
+        Err(e) => Response::new(Value::Null, Err(rpc_error(PARSE_ERROR, e))),
+        Ok(value) => match serde_json::from_value::<Request>(value) {
+            Err(e) => Response::new(Value::Null, Err(rpc_error(INVALID_REQUEST, e))),
+            Ok(request) if request.jsonrpc != "2.0" => Response::new(
+                request.id.unwrap_or_default(),
+                Err(rpc_error(INVALID_REQUEST, "jsonrpc has to be 2.0")),
+            ),
+            Ok(request) => {
+                let result = call(&request.method, request.params).await;
+                Response::new(request.id?, result)
+            }
+        },
+    };
+    Some(serde_json::to_string(&response).expect("JSON-RPC responses are serializable"))
+}
+
+/// Answers newline delimited requests from `reader` on `writer` until the
+/// reader is closed.
+async fn serve_lines<R, W>(reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| VDPMError::RpcError("Failed to read request".into(), e))?
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle(&line).await {
+            writer
+                .write_all(format!("{}\n", response).as_bytes())
+                .await
+                .map_err(|e| VDPMError::RpcError("Failed to write response".into(), e))?;
+            writer
+                .flush()
+                .await
+                .map_err(|e| VDPMError::RpcError("Failed to write response".into(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Serves JSON-RPC on stdin until it is closed, for clients like the
+/// VisiData companion plugin that spawn vdpm.
+pub async fn serve_stdio() -> Result<()> {
+    info!("Serving JSON-RPC on stdin!");
+    serve_lines(tokio::io::stdin(), tokio::io::stdout()).await
+}
+
+/// Binds `socket_path`, replacing the socket of a server that is gone. Only
+/// the user may connect, as clients can install and run any plugin, so the
+/// socket is bound in a private folder and only moved to `socket_path` once
+/// it is restricted.
+async fn bind(socket_path: &Path) -> Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(socket_path) {
+        if !metadata.file_type().is_socket() {
+            return Err(VDPMError::InvalidArguments(format!(
+                "{} exists and is not a socket",
+                socket_path.display()
+            )));
+        }
+        if UnixStream::connect(socket_path).await.is_ok() {
+            return Err(VDPMError::InvalidArguments(format!(
+                "vdpm is already serving on {}",
+                socket_path.display()
+            )));
+        }
+        tokio::fs::remove_file(socket_path).await.map_err(|e| {
+            VDPMError::RpcError(format!("Failed to remove {}", socket_path.display()), e)
+        })?;
+    }
+
+    let bind_error =
+        |e| VDPMError::RpcError(format!("Failed to bind {}", socket_path.display()), e);
+    let bind_folder = tempfile::Builder::new()
+        .prefix(".vdpm-sock")
+        .tempdir_in(socket_path.parent().unwrap_or(Path::new(".")))
+        .map_err(bind_error)?;
+    let bound_path = bind_folder.path().join("vdpm.sock");
+    let listener = UnixListener::bind(&bound_path).map_err(bind_error)?;
+    std::fs::set_permissions(&bound_path, Permissions::from_mode(0o600)).map_err(|e| {
+        VDPMError::RpcError(format!("Failed to restrict {}", socket_path.display()), e)
+    })?;
+    std::fs::rename(&bound_path, socket_path).map_err(bind_error)?;
+    Ok(listener)
+}
+
+async fn accept_clients(listener: &UnixListener) -> Result<()> {
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| VDPMError::RpcError("Failed to accept client".into(), e))?;
+        debug!("JSON-RPC client connected!");
+        tokio::spawn(async move {
+            let (reader, writer) = stream.into_split();
+            if let Err(e) = serve_lines(reader, writer).await {
+                warn!("JSON-RPC client failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Serves JSON-RPC on a Unix domain socket until interrupted. Every client
+/// sends newline delimited requests on its connection, like with `vdpm rpc`.
+pub async fn serve_socket(socket_path: &Path) -> Result<()> {
+    let listener = bind(socket_path).await?;
+    info!("Serving JSON-RPC on {}!", socket_path.display());
+    let result = tokio::select! {
+        result = accept_clients(&listener) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
+    if let Err(e) = std::fs::remove_file(socket_path) {
+        warn!("Failed to remove {}: {}", socket_path.display(), e);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn error_code(request: &str) -> i64 {
+        let response: Value = serde_json::from_str(&handle(request).await.unwrap()).unwrap();
+        response["error"]["code"].as_i64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_invalid_requests() {
+        assert_eq!(error_code("{not json").await, PARSE_ERROR);
+        assert_eq!(error_code(r#"{"id": 1}"#).await, INVALID_REQUEST);
+        assert_eq!(
+            error_code(r#"{"jsonrpc": "1.0", "id": 1, "method": "list"}"#).await,
+            INVALID_REQUEST
+        );
+        assert_eq!(
+            error_code(r#"{"jsonrpc": "2.0", "id": 1, "method": "format-disk"}"#).await,
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            error_code(r#"{"jsonrpc": "2.0", "id": 1, "method": "install", "params": {}}"#).await,
+            INVALID_PARAMS
+        );
+        assert!(
+            handle(r#"{"jsonrpc": "2.0", "method": "format-disk"}"#)
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("vdpm.sock");
+        // a socket file left behind by a server that is gone is replaced
+        drop(std::os::unix::net::UnixListener::bind(&socket_path).unwrap());
+
+        let server = tokio::spawn({
+            let socket_path = socket_path.clone();
+            async move { serve_socket(&socket_path).await }
+        });
+        let mut stream = loop {
+            match UnixStream::connect(&socket_path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        };
+        assert!(matches!(
+            bind(&socket_path).await,
+            Err(VDPMError::InvalidArguments(_))
+        ));
+        assert_eq!(
+            std::fs::metadata(&socket_path)
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o600
+        );
+        // anything but a socket is left alone
+        let file_path = dir.path().join("notes.txt");
+        std::fs::write(&file_path, "keep me").unwrap();
+        assert!(matches!(
+            bind(&file_path).await,
+            Err(VDPMError::InvalidArguments(_))
+        ));
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "keep me");
+
+        stream
+            .write_all(b"{\"jsonrpc\": \"2.0\", \"method\": \"nope\"}\n\n{\"jsonrpc\": \"2.0\", \"id\": 7, \"method\": \"nope\"}\n")
+            .await
+            .unwrap();
+        let (reader, _writer) = stream.into_split();
+        let response = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await
+            .unwrap()
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        server.abort();
+    }
+}
@@ -0,0 +1,264 @@
+use crate::error::{Result, VDPMError};
+use crate::sources::{find_plugin, place_plugin, place_plugin_as};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::info;
+
+/// A plugin installed from a git repository, with the commit the ref resolved to.
+#[derive(Debug, Clone)]
+pub struct GitCheckout {
+    pub plugin_name: String,
+    pub commit: String,
+}
+
+/// Splits `<url>@<ref>` into url and ref. An `@` before the repository path
+/// (e.g. `ssh://git@host/repo.git`) belongs to the url.
+pub fn split_ref(source: &str) -> (String, Option<String>) {
+    match source.rsplit_once('@') {
+        Some((url, git_ref))
+            if !git_ref.is_empty()
+                && url
+                    .split_once("://")
+                    .map_or(url, |(_, location)| location)
+                    .contains('/') =>
+        {
+            (url.to_string(), Some(git_ref.to_string()))
+        }
+        _ => (source.to_string(), None),
+    }
+}
+
+fn cache_folder_name(url: &str) -> String {
+    url.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+async fn run_git(args: &[&str], working_dir: Option<&Path>) -> Result<Vec<u8>> {
+    let mut command = Command::new("git");
+    if let Some(working_dir) = working_dir {
+        command.arg("-C").arg(working_dir);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| VDPMError::GitError(args.join(" "), e.to_string()))?;
+
+    if !output.status.success() {
+        return Err(VDPMError::GitError(
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Clones the repository as a bare mirror into the cache, or refreshes an
+/// existing mirror, and returns its path.
+async fn update_cache(url: &str, cache_folder: &Path) -> Result<PathBuf> {
+    let repository_cache = cache_folder.join(cache_folder_name(url));
+    if repository_cache.exists() {
+        info!("Fetching {} into {}", url, repository_cache.display());
+        run_git(
+            &[
+                "fetch",
+                "--quiet",
+                "--prune",
+                "--tags",
+                "--force",
+                "--",
+                url,
+                "+refs/heads/*:refs/heads/*",
+            ],
+            Some(&repository_cache),
+        )
+        .await?;
+    } else {
+        info!("Cloning {} into {}", url, repository_cache.display());
+        tokio::fs::create_dir_all(cache_folder).await.map_err(|e| {
+            VDPMError::PluginFileError("Failed to create git cache folder".into(), e)
+        })?;
+        let repository_cache_str = repository_cache.to_string_lossy();
+        run_git(
+            &[
+                "clone",
+                "--quiet",
+                "--bare",
+                "--",
+                url,
+                &repository_cache_str,
+            ],
+            None,
+        )
+        .await?;
+    }
+    Ok(repository_cache)
+}
+
+async fn resolve_commit(repository_cache: &Path, git_ref: &str) -> Result<String> {
+    let revision = format!("{}^{{commit}}", git_ref);
+    let commit = run_git(
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            "--end-of-options",
+            &revision,
+        ],
+        Some(repository_cache),
+    )
+    .await
+    .map_err(|_| VDPMError::GitError(format!("rev-parse {}", git_ref), "unknown ref".into()))?;
+    Ok(String::from_utf8_lossy(&commit).trim().to_string())
+}
+
+/// Checks out `git_ref` (a tag, branch or commit, `HEAD` when omitted) of the
+/// repository at `url` and places its plugin module into `plugin_folder`,
+/// named `plugin_name` if given.
+pub async fn install(
+    url: &str,
+    git_ref: Option<&str>,
+    cache_folder: &Path,
+    plugin_folder: &Path,
+    plugin_name: Option<&str>,
+) -> Result<GitCheckout> {
+    // Git would read a leading dash as an option rather than a url or ref.
+    if url.starts_with('-') || git_ref.is_some_and(|git_ref| git_ref.starts_with('-')) {
+        return Err(VDPMError::UnsupportedSource(format!(
+            "{}@{}",
+            url,
+            git_ref.unwrap_or("HEAD")
+        )));
+    }
+
+    let repository_cache = update_cache(url, cache_folder).await?;
+    let commit = resolve_commit(&repository_cache, git_ref.unwrap_or("HEAD")).await?;
+    info!(
+        "Resolved {}@{} to {}",
+        url,
+        git_ref.unwrap_or("HEAD"),
+        commit
+    );
+
+    let archive = run_git(
+        &["archive", "--format=tar", &commit],
+        Some(&repository_cache),
+    )
+    .await?;
+    let checkout_folder = tempfile::tempdir()
+        .map_err(|e| VDPMError::PluginFileError("Failed to create checkout folder".into(), e))?;
+    tar::Archive::new(archive.as_slice())
+        .unpack(checkout_folder.path())
+        .map_err(|e| VDPMError::PluginFileError(format!("Failed to check out {}", commit), e))?;
+
+    let plugin_path = find_plugin(checkout_folder.path())?;
+    let plugin_name = match plugin_name {
+        Some(plugin_name) => place_plugin_as(&plugin_path, plugin_folder, plugin_name)?,
+        None => place_plugin(&plugin_path, plugin_folder)?,
+    };
+    Ok(GitCheckout {
+        plugin_name,
+        commit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn git(args: &[&str], working_dir: &Path) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(working_dir)
+            .args(["-c", "user.name=vdpm", "-c", "user.email=vdpm@localhost"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_split_ref() {
+        assert_eq!(
+            split_ref("https://github.com/user/plugin.git@v1.0"),
+            (
+                "https://github.com/user/plugin.git".to_string(),
+                Some("v1.0".to_string())
+            )
+        );
+        assert_eq!(
+            split_ref("ssh://git@github.com/user/plugin.git"),
+            ("ssh://git@github.com/user/plugin.git".to_string(), None)
+        );
+        assert_eq!(
+            split_ref("ssh://git@github.com/user/plugin.git@feature/x"),
+            (
+                "ssh://git@github.com/user/plugin.git".to_string(),
+                Some("feature/x".to_string())
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_from_bare_repository_at_tag() {
+        let workspace = tempfile::tempdir().unwrap();
+        let work_tree = workspace.path().join("work");
+        let bare_repository = workspace.path().join("hello.git");
+        fs::create_dir(&work_tree).unwrap();
+        git(&["init", "--quiet"], &work_tree);
+        fs::write(work_tree.join("hello.py"), "version = 1").unwrap();
+        fs::write(work_tree.join("README.md"), "hello").unwrap();
+        git(&["add", "."], &work_tree);
+        git(&["commit", "--quiet", "-m", "v1"], &work_tree);
+        git(&["tag", "v1"], &work_tree);
+        fs::write(work_tree.join("hello.py"), "version = 2").unwrap();
+        git(&["commit", "--quiet", "-am", "v2"], &work_tree);
+        git(
+            &[
+                "clone",
+                "--quiet",
+                "--bare",
+                ".",
+                bare_repository.to_str().unwrap(),
+            ],
+            &work_tree,
+        );
+
+        let cache_folder = workspace.path().join("cache");
+        let plugin_folder = workspace.path().join("plugins");
+        let url = format!("file://{}", bare_repository.display());
+        let checkout = install(&url, Some("v1"), &cache_folder, &plugin_folder, None)
+            .await
+            .unwrap();
+
+        assert_eq!(checkout.plugin_name, "hello");
+        assert_eq!(checkout.commit.len(), 40);
+        assert_eq!(
+            fs::read_to_string(plugin_folder.join("hello.py")).unwrap(),
+            "version = 1"
+        );
+        assert!(
+            install(&url, Some("missing"), &cache_folder, &plugin_folder, None)
+                .await
+                .is_err()
+        );
+        assert!(matches!(
+            install(
+                &url,
+                Some("--output=x"),
+                &cache_folder,
+                &plugin_folder,
+                None
+            )
+            .await,
+            Err(VDPMError::UnsupportedSource(_))
+        ));
+        assert!(matches!(
+            install("--upload-pack=x", None, &cache_folder, &plugin_folder, None).await,
+            Err(VDPMError::UnsupportedSource(_))
+        ));
+    }
+}
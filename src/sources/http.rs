@@ -0,0 +1,367 @@
+use crate::error::{Result, VDPMError};
+use crate::sources::{Integrity, find_plugin, place_plugin, place_plugin_as};
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// A plugin placed from a download, with the trusted key that signed it.
+#[derive(Debug)]
+pub struct Download {
+    pub plugin_name: String,
+    pub signed_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DownloadKind {
+    Module,
+    Zip,
+    TarGz,
+}
+
+impl DownloadKind {
+    fn from_file_name(file_name: &str) -> Option<Self> {
+        if file_name.ends_with(".py") {
+            Some(DownloadKind::Module)
+        } else if file_name.ends_with(".zip") {
+            Some(DownloadKind::Zip)
+        } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Some(DownloadKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+fn file_name_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    path.rsplit('/').next().unwrap_or_default().to_string()
+}
+
+/// Fetches `url`, authenticating with a bearer `token` if given.
+pub async fn fetch(url: &str, token: Option<&str>) -> Result<Vec<u8>> {
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| VDPMError::DownloadError(url.to_string(), e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(VDPMError::DownloadStatusError(
+            url.to_string(),
+            status.as_u16(),
+        ));
+    }
+
+    let content = response
+        .bytes()
+        .await
+        .map_err(|e| VDPMError::DownloadError(url.to_string(), e))?;
+    Ok(content.to_vec())
+}
+
+/// Reads an `http(s)://` url, a `file://` url or a local path.
+pub async fn fetch_location(location: &str, token: Option<&str>) -> Result<Vec<u8>> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return fetch(location, token).await;
+    }
+    let path = location.strip_prefix("file://").unwrap_or(location);
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| VDPMError::PluginFileError(format!("Failed to read {}", path), e))
+}
+
+pub async fn download(url: &str, token: Option<&str>, destination: &Path) -> Result<()> {
+    let content = fetch(url, token).await?;
+    tokio::fs::write(destination, &content).await.map_err(|e| {
+        VDPMError::PluginFileError(format!("Failed to save download from {}", url), e)
+    })?;
+    Ok(())
+}
+
+/// A downloaded `.py` has to be python source, not e.g. an HTML error page
+/// served with a 200 status.
+fn validate_python_source(path: &Path) -> Result<()> {
+    let content = fs::read(path)
+        .map_err(|e| VDPMError::PluginFileError(format!("Failed to read {}", path.display()), e))?;
+    let is_python_source = std::str::from_utf8(&content).is_ok_and(|source| {
+        let source = source.trim_start().to_lowercase();
+        !source.starts_with("<!doctype") && !source.starts_with("<html")
+    });
+
+    if !is_python_source {
+        return Err(VDPMError::InvalidPlugin(
+            path.display().to_string(),
+            "downloaded file is not python source".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn extract(archive_path: &Path, kind: DownloadKind, destination: &Path) -> Result<()> {
+    let archive_error = |e: std::io::Error| {
+        VDPMError::PluginFileError(format!("Failed to extract {}", archive_path.display()), e)
+    };
+    let archive = File::open(archive_path).map_err(archive_error)?;
+
+    match kind {
+        DownloadKind::Zip => zip::ZipArchive::new(archive)
+            .and_then(|mut zip_archive| zip_archive.extract(destination))
+            .map_err(|e| archive_error(std::io::Error::other(e))),
+        DownloadKind::TarGz => tar::Archive::new(GzDecoder::new(archive))
+            .unpack(destination)
+            .map_err(archive_error),
+        DownloadKind::Module => Ok(()),
+    }
+}
+
+/// Downloads a `.py` module or a `.zip`/`.tar.gz` archive containing a plugin
+/// into a temporary folder and places the plugin into `plugin_folder`, named
+/// `plugin_name` if given. The download is rejected before it is unpacked
+/// when it fails the `integrity` checks. `token` authenticates it with the
+/// registry the plugin was resolved from.
+pub async fn install(
+    url: &str,
+    token: Option<&str>,
+    plugin_folder: &Path,
+    plugin_name: Option<&str>,
+    integrity: &Integrity,
+) -> Result<Download> {
+    let file_name = file_name_from_url(url);
+    let kind = DownloadKind::from_file_name(&file_name)
+        .ok_or_else(|| VDPMError::UnsupportedSource(url.to_string()))?;
+
+    let download_folder = tempfile::tempdir()
+        .map_err(|e| VDPMError::PluginFileError("Failed to create download folder".into(), e))?;
+    let download_path = download_folder.path().join(&file_name);
+    info!("Downloading {} to {}", url, download_path.display());
+    download(url, token, &download_path).await?;
+    let signed_by = integrity.check(&download_path)?;
+
+    let plugin_path: PathBuf = match kind {
+        DownloadKind::Module => {
+            validate_python_source(&download_path)?;
+            download_path
+        }
+        DownloadKind::Zip | DownloadKind::TarGz => {
+            let extract_folder = download_folder.path().join("extracted");
+            extract(&download_path, kind, &extract_folder)?;
+            find_plugin(&extract_folder)?
+        }
+    };
+
+    let plugin_name = match plugin_name {
+        Some(plugin_name) => place_plugin_as(&plugin_path, plugin_folder, plugin_name)?,
+        None => place_plugin(&plugin_path, plugin_folder)?,
+    };
+    Ok(Download {
+        plugin_name,
+        signed_by,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::sources::signature::tests::signing_key;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serves a single canned response per connection on a random local port,
+    /// standing in for a real plugin host.
+    pub(crate) fn serve(status: u16, body: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().filter_map(std::result::Result::ok) {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let header = format!(
+                    "HTTP/1.1 {} OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_install_module_from_url() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let url = format!(
+            "{}/plugins/hello.py?raw=true",
+            serve(200, b"print(1)".to_vec())
+        );
+
+        assert_eq!(
+            install(
+                &url,
+                None,
+                plugin_folder.path(),
+                None,
+                &Integrity::default()
+            )
+            .await
+            .unwrap()
+            .plugin_name,
+            "hello"
+        );
+        assert_eq!(
+            fs::read_to_string(plugin_folder.path().join("hello.py")).unwrap(),
+            "print(1)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_tar_gz_from_url() {
+        let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let content = b"print(1)";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive
+            .append_data(
+                &mut header,
+                "hello-main/hello_pkg/__init__.py",
+                &content[..],
+            )
+            .unwrap();
+        let body = archive.into_inner().unwrap().finish().unwrap();
+
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let url = format!("{}/hello.tar.gz", serve(200, body));
+
+        assert_eq!(
+            install(
+                &url,
+                None,
+                plugin_folder.path(),
+                None,
+                &Integrity::default()
+            )
+            .await
+            .unwrap()
+            .plugin_name,
+            "hello_pkg"
+        );
+        assert!(plugin_folder.path().join("hello_pkg/__init__.py").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_integrity_failure_aborts_install() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let url = format!("{}/hello.py", serve(200, b"print(1)".to_vec()));
+        let sha256 = crate::fs::checksum::sha256_bytes(b"print(1)");
+        let (publisher, sign) = signing_key("publisher");
+        let (_, sign_with_other) = signing_key("other");
+
+        let failing_integrities = [
+            Integrity {
+                sha256: Some(sha256.replace('7', "8")),
+                ..Integrity::default()
+            },
+            Integrity {
+                signature: Some(sign_with_other(b"print(1)")),
+                trusted_keys: vec![publisher.clone()],
+                ..Integrity::default()
+            },
+        ];
+        for integrity in failing_integrities {
+            assert!(matches!(
+                install(&url, None, plugin_folder.path(), None, &integrity).await,
+                Err(VDPMError::ChecksumMismatch(..) | VDPMError::SignatureError(..))
+            ));
+            assert!(fs::read_dir(plugin_folder.path()).unwrap().next().is_none());
+        }
+
+        let integrity = Integrity {
+            sha256: Some(sha256.to_uppercase()),
+            signature: Some(sign(b"print(1)")),
+            trusted_keys: vec![publisher],
+        };
+        let download = install(&url, None, plugin_folder.path(), None, &integrity)
+            .await
+            .unwrap();
+        assert_eq!(download.plugin_name, "hello");
+        assert_eq!(download.signed_by.as_deref(), Some("publisher"));
+    }
+
+    #[tokio::test]
+    async fn test_download_failure_reports_status_code() {
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let url = format!("{}/missing.py", serve(404, Vec::new()));
+
+        assert!(matches!(
+            install(
+                &url,
+                None,
+                plugin_folder.path(),
+                None,
+                &Integrity::default()
+            )
+            .await,
+            Err(VDPMError::DownloadStatusError(_, 404))
+        ));
+        assert!(fs::read_dir(plugin_folder.path()).unwrap().next().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_install_sends_registry_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().filter_map(std::result::Result::ok) {
+                let mut request = [0u8; 4096];
+                let length = stream.read(&mut request).unwrap_or_default();
+                let request = String::from_utf8_lossy(&request[..length]).to_lowercase();
+                let (status, body) = if request.contains("authorization: bearer secret\r\n") {
+                    ("200 OK", "print(1)")
+                } else {
+                    ("401 Unauthorized", "")
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let url = format!("http://{}/hello.py", address);
+
+        assert!(matches!(
+            install(
+                &url,
+                None,
+                plugin_folder.path(),
+                None,
+                &Integrity::default()
+            )
+            .await,
+            Err(VDPMError::DownloadStatusError(_, 401))
+        ));
+        let download = install(
+            &url,
+            Some("secret"),
+            plugin_folder.path(),
+            None,
+            &Integrity::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(download.plugin_name, "hello");
+    }
+}
@@ -0,0 +1,12 @@
+use crate::error::Result;
+use crate::sources::{place_plugin, place_plugin_as};
+use std::path::Path;
+
+/// Installs a plugin from a local `.py` file or package directory, named
+/// `plugin_name` if given.
+pub fn install(path: &Path, plugin_folder: &Path, plugin_name: Option<&str>) -> Result<String> {
+    match plugin_name {
+        Some(plugin_name) => place_plugin_as(path, plugin_folder, plugin_name),
+        None => place_plugin(path, plugin_folder),
+    }
+}
@@ -0,0 +1,395 @@
+use crate::config_loader::TrustedKey;
+use crate::error::{Result, VDPMError};
+use crate::fs::checksum::sha256_bytes;
+use crate::fs::operations::{copy_recursively, list_files_by_extension, list_package_dirs};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+pub mod git;
+pub mod http;
+pub mod local;
+pub mod signature;
+
+/// Where `vdpm install` takes a plugin from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallSource {
+    Local(PathBuf),
+    Url(String),
+    Git {
+        url: String,
+        git_ref: Option<String>,
+    },
+    /// `[<registry>/]<name>[@<version>]` looked up in the registry indexes
+    Index {
+        registry: Option<String>,
+        name: String,
+        version: Option<String>,
+    },
+}
+
+impl InstallSource {
+    pub fn parse(source: &str) -> Result<Self> {
+        Self::parse_in(source, Path::new(""))
+    }
+
+    /// Like [`InstallSource::parse`], with a relative path resolved against
+    /// `base_dir` instead of the current directory.
+    pub fn parse_in(source: &str, base_dir: &Path) -> Result<Self> {
+        if let Some(git_source) = source.strip_prefix("git+") {
+            let (url, git_ref) = git::split_ref(git_source);
+            return Ok(InstallSource::Git { url, git_ref });
+        }
+
+        if source.starts_with("http://") || source.starts_with("https://") {
+            return Ok(InstallSource::Url(source.to_string()));
+        }
+
+        let path = base_dir.join(source);
+        if path.exists() {
+            let path = path.canonicalize().map_err(|e| {
+                VDPMError::PluginFileError(format!("Failed to resolve {}", source), e)
+            })?;
+            return Ok(InstallSource::Local(path));
+        }
+
+        let (registry, plugin) = match source.split_once('/') {
+            Some((registry, plugin)) if is_valid_registry_name(registry) => {
+                (Some(registry.to_string()), plugin)
+            }
+            _ => (None, source),
+        };
+        let (name, version) = match plugin.split_once('@') {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (plugin, None),
+        };
+        if is_valid_plugin_name(name) {
+            return Ok(InstallSource::Index {
+                registry,
+                name: name.to_string(),
+                version,
+            });
+        }
+
+        Err(VDPMError::UnsupportedSource(source.to_string()))
+    }
+}
+
+impl fmt::Display for InstallSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstallSource::Local(path) => write!(f, "{}", path.display()),
+            InstallSource::Url(url) => write!(f, "{}", url),
+            InstallSource::Git { url, git_ref: None } => write!(f, "git+{}", url),
+            InstallSource::Git {
+                url,
+                git_ref: Some(git_ref),
+            } => write!(f, "git+{}@{}", url, git_ref),
+            InstallSource::Index {
+                registry,
+                name,
+                version,
+            } => {
+                if let Some(registry) = registry {
+                    write!(f, "{}/", registry)?;
+                }
+                write!(f, "{}", name)?;
+                if let Some(version) = version {
+                    write!(f, "@{}", version)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+pub fn is_valid_registry_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn is_valid_plugin_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_module(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "py")
+}
+
+fn is_package(path: &Path) -> bool {
+    path.is_dir() && path.join("__init__.py").is_file()
+}
+
+fn not_a_plugin(path: &Path) -> VDPMError {
+    VDPMError::InvalidPlugin(
+        path.display().to_string(),
+        "expected a .py file or a package directory with __init__.py".into(),
+    )
+}
+
+/// Checks that `path` is a python module or package VisiData can import and
+/// returns the plugin name it would be imported as.
+pub fn validate_plugin_path(path: &Path) -> Result<String> {
+    let name = if is_module(path) {
+        path.file_stem()
+    } else if is_package(path) {
+        path.file_name()
+    } else {
+        return Err(not_a_plugin(path));
+    }
+    .and_then(|name| name.to_str())
+    .unwrap_or_default()
+    .to_string();
+
+    if !is_valid_plugin_name(&name) {
+        return Err(VDPMError::InvalidPlugin(
+            path.display().to_string(),
+            format!("{} is not a valid python module name", name),
+        ));
+    }
+    Ok(name)
+}
+
+/// Looks for the plugin inside an extracted archive or checkout. Archives
+/// usually wrap their content in a single top level folder, which is descended
+/// into until exactly one plugin module or package is found.
+pub fn find_plugin(folder: &Path) -> Result<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(folder)
+        .map_err(|e| VDPMError::PluginFileError(format!("Failed to read {}", folder.display()), e))?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| !name.starts_with('.'))
+        })
+        .collect();
+    entries.sort();
+
+    let plugins: Vec<&PathBuf> = entries
+        .iter()
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name != "setup.py" && name != "__init__.py")
+                && validate_plugin_path(path).is_ok()
+        })
+        .collect();
+
+    match (plugins.as_slice(), entries.as_slice()) {
+        ([plugin], _) => Ok(plugin.to_path_buf()),
+        ([], [single_entry]) if single_entry.is_dir() => find_plugin(single_entry),
+        ([], _) => Err(VDPMError::InvalidPlugin(
+            folder.display().to_string(),
+            "no python module or package found".into(),
+        )),
+        (_, _) => Err(VDPMError::InvalidPlugin(
+            folder.display().to_string(),
+            format!("found {} candidate plugins, expected one", plugins.len()),
+        )),
+    }
+}
+
+/// What a downloaded or local plugin file is checked against before anything
+/// is placed into the plugin folder.
+#[derive(Debug, Clone, Default)]
+pub struct Integrity {
+    /// The sha256 published in the registry index
+    pub sha256: Option<String>,
+    /// Detached minisign signature, which has to be made by one of `trusted_keys`
+    pub signature: Option<String>,
+    pub trusted_keys: Vec<TrustedKey>,
+}
+
+impl Integrity {
+    pub fn is_empty(&self) -> bool {
+        self.sha256.is_none() && self.signature.is_none()
+    }
+
+    /// Returns the name of the trusted key that signed the file, if signed.
+    pub fn check(&self, path: &Path) -> Result<Option<String>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let content = fs::read(path).map_err(|e| {
+            VDPMError::PluginFileError(format!("Failed to read {}", path.display()), e)
+        })?;
+
+        if let Some(expected_sha256) = &self.sha256 {
+            let sha256 = sha256_bytes(&content);
+            if !sha256.eq_ignore_ascii_case(expected_sha256.trim()) {
+                return Err(VDPMError::ChecksumMismatch(
+                    path.display().to_string(),
+                    expected_sha256.clone(),
+                    sha256,
+                ));
+            }
+        }
+
+        self.signature
+            .as_deref()
+            .map(|signature| {
+                signature::verify(
+                    &path.display().to_string(),
+                    &content,
+                    signature,
+                    &self.trusted_keys,
+                )
+            })
+            .transpose()
+    }
+}
+
+/// Copies a validated plugin module or package into `plugin_folder`. The copy
+/// goes to a staging path first and is renamed into place, so a failed copy
+/// never leaves a half written plugin behind.
+pub fn place_plugin(plugin_path: &Path, plugin_folder: &Path) -> Result<String> {
+    let plugin_name = validate_plugin_path(plugin_path)?;
+    place_plugin_as(plugin_path, plugin_folder, &plugin_name)
+}
+
+/// Like [`place_plugin`], but installs the plugin under `plugin_name` instead
+/// of its file name, e.g. for versioned download names like `hello-0.2.0.py`.
+pub fn place_plugin_as(
+    plugin_path: &Path,
+    plugin_folder: &Path,
+    plugin_name: &str,
+) -> Result<String> {
+    if !is_module(plugin_path) && !is_package(plugin_path) {
+        return Err(not_a_plugin(plugin_path));
+    }
+    if !is_valid_plugin_name(plugin_name) {
+        return Err(VDPMError::InvalidPlugin(
+            plugin_path.display().to_string(),
+            format!("{} is not a valid python module name", plugin_name),
+        ));
+    }
+    let plugin_name = plugin_name.to_string();
+
+    if list_files_by_extension(plugin_folder, "py".to_string()).contains(&plugin_name)
+        || list_package_dirs(plugin_folder).contains(&plugin_name)
+        || plugin_folder.join(&plugin_name).exists()
+    {
+        return Err(VDPMError::PluginAlreadyInstalled(plugin_name));
+    }
+
+    let file_name = if plugin_path.is_dir() {
+        plugin_name.clone()
+    } else {
+        format!("{}.py", plugin_name)
+    };
+    let staging_path = plugin_folder.join(format!(".{}.vdpm-tmp", file_name));
+    let target_path = plugin_folder.join(&file_name);
+
+    fs::create_dir_all(plugin_folder)
+        .map_err(|e| VDPMError::PluginFileError("Failed to create plugin folder".into(), e))?;
+    if let Err(e) = copy_recursively(plugin_path, &staging_path)
+        .and_then(|_| fs::rename(&staging_path, &target_path))
+    {
+        let _ = fs::remove_dir_all(&staging_path).or_else(|_| fs::remove_file(&staging_path));
+        return Err(VDPMError::PluginFileError(
+            format!(
+                "Failed to copy {} into plugin folder",
+                plugin_path.display()
+            ),
+            e,
+        ));
+    }
+
+    Ok(plugin_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_install_source() {
+        assert_eq!(
+            InstallSource::parse("https://example.com/hello.py").unwrap(),
+            InstallSource::Url("https://example.com/hello.py".into())
+        );
+        assert_eq!(
+            InstallSource::parse("git+file:///tmp/hello.git@v1").unwrap(),
+            InstallSource::Git {
+                url: "file:///tmp/hello.git".into(),
+                git_ref: Some("v1".into())
+            }
+        );
+        assert_eq!(
+            InstallSource::parse("hello@0.2.0").unwrap(),
+            InstallSource::Index {
+                registry: None,
+                name: "hello".into(),
+                version: Some("0.2.0".into())
+            }
+        );
+        assert_eq!(
+            InstallSource::parse("my-registry/hello").unwrap(),
+            InstallSource::Index {
+                registry: Some("my-registry".into()),
+                name: "hello".into(),
+                version: None
+            }
+        );
+        assert!(InstallSource::parse("not a plugin").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hello.py"), "").unwrap();
+        assert_eq!(
+            InstallSource::parse_in("hello.py", dir.path()).unwrap(),
+            InstallSource::Local(dir.path().join("hello.py").canonicalize().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_validate_plugin_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let module = dir.path().join("hello.py");
+        fs::write(&module, "").unwrap();
+        assert_eq!(validate_plugin_path(&module).unwrap(), "hello");
+
+        let package = dir.path().join("hello_pkg");
+        fs::create_dir(&package).unwrap();
+        assert!(validate_plugin_path(&package).is_err());
+        fs::write(package.join("__init__.py"), "").unwrap();
+        assert_eq!(validate_plugin_path(&package).unwrap(), "hello_pkg");
+
+        let invalid = dir.path().join("hello-world.py");
+        fs::write(&invalid, "").unwrap();
+        assert!(validate_plugin_path(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_place_plugin_detects_collisions() {
+        let source = tempfile::tempdir().unwrap();
+        let plugin_folder = tempfile::tempdir().unwrap();
+        let module = source.path().join("hello.py");
+        fs::write(&module, "print('hello')").unwrap();
+
+        assert_eq!(
+            place_plugin(&module, plugin_folder.path()).unwrap(),
+            "hello"
+        );
+        assert_eq!(
+            fs::read_to_string(plugin_folder.path().join("hello.py")).unwrap(),
+            "print('hello')"
+        );
+        assert!(matches!(
+            place_plugin(&module, plugin_folder.path()),
+            Err(VDPMError::PluginAlreadyInstalled(_))
+        ));
+
+        let versioned_module = source.path().join("hello-0.2.0.py");
+        fs::write(&versioned_module, "").unwrap();
+        assert!(place_plugin(&versioned_module, plugin_folder.path()).is_err());
+        assert_eq!(
+            place_plugin_as(&versioned_module, plugin_folder.path(), "hello_versioned").unwrap(),
+            "hello_versioned"
+        );
+    }
+}
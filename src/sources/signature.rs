@@ -0,0 +1,91 @@
+use crate::config_loader::TrustedKey;
+use crate::error::{Result, VDPMError};
+use minisign_verify::{PublicKey, Signature};
+
+/// Checks a detached minisign signature over `content` and returns the name
+/// of the trusted key that made it. `source` only names the plugin in errors.
+pub fn verify(
+    source: &str,
+    content: &[u8],
+    signature: &str,
+    trusted_keys: &[TrustedKey],
+) -> Result<String> {
+    let signature = Signature::decode(signature)
+        .map_err(|e| VDPMError::SignatureError(source.to_string(), e.to_string()))?;
+
+    for trusted_key in trusted_keys {
+        let public_key = PublicKey::from_base64(trusted_key.public_key.trim()).map_err(|e| {
+            VDPMError::InvalidConfig(format!(
+                "trusted key({}) is not a minisign public key: {}",
+                trusted_key.name, e
+            ))
+        })?;
+        if public_key.verify(content, &signature, false).is_ok() {
+            return Ok(trusted_key.name.clone());
+        }
+    }
+
+    Err(VDPMError::SignatureError(
+        source.to_string(),
+        "not signed by any trusted key".into(),
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use minisign::KeyPair;
+    use std::io::Cursor;
+
+    /// A trusted key named `name` and a function signing content with it.
+    pub(crate) fn signing_key(name: &str) -> (TrustedKey, impl Fn(&[u8]) -> String) {
+        let key_pair = KeyPair::generate_unencrypted_keypair().unwrap();
+        let trusted_key = TrustedKey {
+            name: name.into(),
+            public_key: key_pair.pk.to_base64(),
+        };
+        let sign = move |content: &[u8]| {
+            minisign::sign(None, &key_pair.sk, Cursor::new(content), None, None)
+                .unwrap()
+                .into_string()
+        };
+        (trusted_key, sign)
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let (publisher, sign) = signing_key("publisher");
+        let (other, sign_with_other) = signing_key("other");
+        let signature = sign(b"print(1)");
+
+        assert_eq!(
+            verify(
+                "hello",
+                b"print(1)",
+                &signature,
+                &[other.clone(), publisher.clone()]
+            )
+            .unwrap(),
+            "publisher"
+        );
+        assert!(matches!(
+            verify(
+                "hello",
+                b"print(2)",
+                &signature,
+                std::slice::from_ref(&publisher)
+            ),
+            Err(VDPMError::SignatureError(..))
+        ));
+        assert!(matches!(
+            verify(
+                "hello",
+                b"print(1)",
+                &sign_with_other(b"print(1)"),
+                &[publisher]
+            ),
+            Err(VDPMError::SignatureError(..))
+        ));
+        assert!(verify("hello", b"print(1)", "not a signature", &[other]).is_err());
+    }
+}
@@ -1,9 +1,9 @@
+use crate::error::{Result, VDPMError};
+use crate::fs::checksum::sha256_bytes;
 use directories::BaseDirs;
+use serde::Serialize;
 use std::path::PathBuf;
 
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
-
 pub fn get_home_dir() -> PathBuf {
     if let Some(base_dirs) = BaseDirs::new() {
         base_dirs.home_dir().to_path_buf()
@@ -12,19 +12,42 @@ pub fn get_home_dir() -> PathBuf {
     }
 }
 
-pub fn hash<T: Hash>(value: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    value.hash(&mut s);
-    s.finish()
+/// A content digest that is stable across runs, platforms and vdpm versions,
+/// so it can be persisted and compared later: the hex sha256 of the value's
+/// canonical JSON, i.e. compact JSON with object keys sorted, which makes the
+/// digest independent of struct field order as well.
+pub fn hash<T: Serialize>(value: &T) -> Result<String> {
+    let canonical_value = serde_json::to_value(value)
+        .map_err(|e| VDPMError::HashError("Failed to serialize value".into(), e))?;
+    let canonical_json = serde_json::to_vec(&canonical_value)
+        .map_err(|e| VDPMError::HashError("Failed to serialize value".into(), e))?;
+    Ok(sha256_bytes(&canonical_json))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::test_fixtures::{plugin, registry};
 
     #[test]
     fn test_hash() {
-        println!("{}", hash(&String::from("vdpm")));
-        assert!(2047530091333027188 == hash(&String::from("vdpm")))
+        assert_eq!(
+            hash(&String::from("vdpm")).unwrap(),
+            "8e14093965a6555807bfcbea3e03e8d0839ffba7f1d4cc79a34e94650ad0ed82"
+        );
+
+        let enabled_registry = registry([plugin("hello", true)]);
+        assert_eq!(
+            hash(&enabled_registry).unwrap(),
+            sha256_bytes(
+                br#"{"plugins":{"hello":{"action":null,"commit":null,"description":null,"enabled":true,"error":null,"installed":true,"installed_at":null,"name":"hello","pinned":false,"sha256":null,"signed_by":null,"size":null,"source":null,"version":null}}}"#
+            )
+        );
+
+        let disabled_registry = registry([plugin("hello", false)]);
+        assert_ne!(
+            hash(&enabled_registry).unwrap(),
+            hash(&disabled_registry).unwrap()
+        );
     }
 }